@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+
+use crate::config::{AccountConfig, AppConfig};
+
+const KEYRING_SERVICE: &str = "ghtray";
+
+/// Where a token was found, in lookup order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenSource {
+    Env(&'static str),
+    Config,
+    Keyring,
+}
+
+impl TokenSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TokenSource::Env(var) => var,
+            TokenSource::Config => "config file",
+            TokenSource::Keyring => "OS keyring",
+        }
+    }
+}
+
+/// Find a token for `account`, following gh's conventions: `GH_TOKEN` /
+/// `GITHUB_TOKEN` for github.com, `GH_ENTERPRISE_TOKEN` /
+/// `GITHUB_ENTERPRISE_TOKEN` for other hosts. Then the config file, then the
+/// OS keyring. Env vars and host-wide tokens only apply to accounts without
+/// an explicit login, so they can't shadow each other.
+pub fn resolve_token(config: &AppConfig, account: &AccountConfig) -> Option<(String, TokenSource)> {
+    resolve_token_from(
+        config,
+        account,
+        |var| std::env::var(var).ok(),
+        keyring_token,
+    )
+}
+
+/// `resolve_token` with the env and keyring lookups passed in.
+fn resolve_token_from(
+    config: &AppConfig,
+    account: &AccountConfig,
+    env: impl Fn(&str) -> Option<String>,
+    keyring: impl Fn(&str) -> Option<String>,
+) -> Option<(String, TokenSource)> {
+    let host = config.host_config(&account.host);
+    let shared = account.login.is_none();
+
+    let vars = if host.is_default() {
+        ["GH_TOKEN", "GITHUB_TOKEN"]
+    } else {
+        ["GH_ENTERPRISE_TOKEN", "GITHUB_ENTERPRISE_TOKEN"]
+    };
+    for var in vars.into_iter().filter(|_| shared) {
+        if let Some(token) = env(var)
+            && !token.trim().is_empty()
+        {
+            return Some((token.trim().to_string(), TokenSource::Env(var)));
+        }
+    }
+
+    let host_token = host
+        .token
+        .as_deref()
+        .or(config.github_token.as_deref().filter(|_| host.is_default()))
+        .filter(|_| shared);
+    if let Some(token) = account.token.as_deref().or(host_token)
+        && !token.trim().is_empty()
+    {
+        return Some((token.trim().to_string(), TokenSource::Config));
+    }
+
+    keyring(&account.keyring_user()).map(|token| (token, TokenSource::Keyring))
+}
+
+/// `user` is the keyring entry name, see `AccountConfig::keyring_user`.
+fn keyring_entry(user: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, user).context("Failed to open OS keyring")
+}
+
+pub fn keyring_token(user: &str) -> Option<String> {
+    keyring_entry(user)
+        .ok()?
+        .get_password()
+        .ok()
+        .filter(|t| !t.is_empty())
+}
+
+pub fn store_keyring_token(user: &str, token: &str) -> Result<()> {
+    keyring_entry(user)?
+        .set_password(token)
+        .context("Failed to store token in OS keyring")
+}
+
+pub fn delete_keyring_token(user: &str) -> Result<()> {
+    match keyring_entry(user)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e).context("Failed to remove token from OS keyring"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DEFAULT_HOST;
+
+    fn resolve(
+        config: &AppConfig,
+        env: Option<&str>,
+        keyring: Option<&str>,
+    ) -> Option<(String, TokenSource)> {
+        let account = AccountConfig::for_host(DEFAULT_HOST);
+        resolve_token_from(
+            config,
+            &account,
+            |var| env.filter(|_| var == "GH_TOKEN").map(str::to_string),
+            |user| keyring.filter(|_| user == DEFAULT_HOST).map(str::to_string),
+        )
+    }
+
+    #[test]
+    fn resolves_env_then_config_then_keyring() {
+        let with_config = AppConfig {
+            github_token: Some("from-config".to_string()),
+            ..AppConfig::default()
+        };
+        assert_eq!(
+            resolve(&with_config, Some("from-env"), Some("from-keyring")),
+            Some(("from-env".to_string(), TokenSource::Env("GH_TOKEN")))
+        );
+        assert_eq!(
+            resolve(&with_config, None, Some("from-keyring")),
+            Some(("from-config".to_string(), TokenSource::Config))
+        );
+        assert_eq!(
+            resolve(&AppConfig::default(), None, Some("from-keyring")),
+            Some(("from-keyring".to_string(), TokenSource::Keyring))
+        );
+        assert_eq!(resolve(&AppConfig::default(), Some("  "), None), None);
+    }
+}
@@ -0,0 +1,1024 @@
+use anyhow::{Context, Result, bail};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::auth;
+use crate::config::{AccountConfig, AppConfig, AuthSource, DEFAULT_HOST};
+use crate::github::{self, GhStatus};
+
+// ── Backend trait ───────────────────────────────────────────────────────────
+
+/// Source of GitHub data. Everything that touches the network goes through
+/// this trait so the fetch → categorize → diff pipeline can run offline.
+pub trait GitHubBackend: Send + Sync {
+    /// Short human-readable name ("gh CLI", "Demo", ...)
+    fn name(&self) -> &'static str;
+
+    /// GitHub host this backend talks to ("github.com" or a GHES hostname)
+    fn host(&self) -> &str {
+        DEFAULT_HOST
+    }
+
+    /// Whether this backend talks to the real GitHub API. Offline backends
+    /// don't persist state between runs.
+    fn is_live(&self) -> bool {
+        true
+    }
+
+    /// Is the backend usable (installed, authenticated)?
+    fn status(&self) -> GhStatus;
+
+    /// What the user should do when `status()` reports not authenticated.
+    fn login_hint(&self) -> &'static str {
+        ""
+    }
+
+    /// Run a GraphQL query and return the raw JSON response body.
+    fn graphql(&self, query: &str) -> Result<Vec<u8>>;
+
+    /// Call a REST endpoint (`path` is relative to the API root, e.g.
+    /// "notifications"). 304 comes back as a response; other non-2xx
+    /// statuses are errors.
+    fn rest(&self, method: &str, path: &str, headers: &[(&str, &str)]) -> Result<RestResponse> {
+        let _ = (method, path, headers);
+        bail!("The {} backend doesn't support REST calls", self.name())
+    }
+
+    /// Make sure avatars for these authors are cached in `avatars_dir()`.
+    fn ensure_avatars(&self, authors: &[String]);
+}
+
+/// Status, headers and body of a REST call.
+#[derive(Debug, Clone, Default)]
+pub struct RestResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl RestResponse {
+    /// Value of the first header called `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Parse `gh api -i` output: status line, headers, blank line, body.
+    fn from_http_text(output: &[u8]) -> Option<Self> {
+        let head_end = output.windows(4).position(|w| w == b"\r\n\r\n");
+        let (head, body) = match head_end {
+            Some(end) => (&output[..end], &output[end + 4..]),
+            None => {
+                let end = output.windows(2).position(|w| w == b"\n\n")?;
+                (&output[..end], &output[end + 2..])
+            }
+        };
+        let head = String::from_utf8_lossy(head);
+        let mut lines = head.lines();
+        let status = lines
+            .next()?
+            .strip_prefix("HTTP/")?
+            .split_whitespace()
+            .nth(1)?
+            .parse()
+            .ok()?;
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect();
+        Some(Self {
+            status,
+            headers,
+            body: body.to_vec(),
+        })
+    }
+}
+
+// ── gh CLI path resolution ─────────────────────────────────────────────────
+
+static GH_PATH: OnceLock<String> = OnceLock::new();
+
+/// Resolve the full path to `gh` CLI. Searches common Homebrew/system paths
+/// since bundled macOS apps don't inherit the user's shell PATH.
+fn gh_bin() -> &'static str {
+    GH_PATH.get_or_init(|| {
+        let candidates = [
+            "/opt/homebrew/bin/gh",          // Apple Silicon Homebrew
+            "/usr/local/bin/gh",             // Intel Homebrew
+            "/usr/bin/gh",                   // System
+            "/run/current-system/sw/bin/gh", // NixOS
+        ];
+
+        // Try common known paths first
+        for path in &candidates {
+            if std::path::Path::new(path).exists() {
+                return path.to_string();
+            }
+        }
+
+        // Fallback: ask the user's login shell where gh lives
+        if let Ok(output) = Command::new("/bin/sh")
+            .args(["-l", "-c", "which gh"])
+            .output()
+        {
+            let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !path.is_empty() && std::path::Path::new(&path).exists() {
+                return path;
+            }
+        }
+
+        // Last resort: hope it's in PATH
+        "gh".to_string()
+    })
+}
+
+// ── gh CLI backend ──────────────────────────────────────────────────────────
+
+/// Talks to GitHub through `gh api graphql`, reusing the user's `gh auth` session.
+pub struct GhCliBackend {
+    hostname: String,
+    /// gh account to run as instead of the active one
+    user: Option<String>,
+}
+
+impl GhCliBackend {
+    pub fn new(hostname: &str, user: Option<&str>) -> Self {
+        Self {
+            hostname: hostname.to_string(),
+            user: user.map(str::to_string),
+        }
+    }
+
+    /// Token of `self.user` from gh's credential store. gh has no per-command
+    /// account switch, so queries for a non-active account pass this token
+    /// through the environment instead.
+    fn user_token(&self) -> Result<Option<String>> {
+        let Some(user) = &self.user else {
+            return Ok(None);
+        };
+        let output = Command::new(gh_bin())
+            .args([
+                "auth",
+                "token",
+                "--hostname",
+                &self.hostname,
+                "--user",
+                user,
+            ])
+            .output()
+            .context("Failed to execute `gh` CLI. Is it installed?")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("gh CLI is not authenticated as {user}: {}", stderr.trim());
+        }
+        Ok(Some(
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        ))
+    }
+
+    fn token_env_var(&self) -> &'static str {
+        if self.hostname == DEFAULT_HOST {
+            "GH_TOKEN"
+        } else {
+            "GH_ENTERPRISE_TOKEN"
+        }
+    }
+}
+
+impl GitHubBackend for GhCliBackend {
+    fn name(&self) -> &'static str {
+        "gh CLI"
+    }
+
+    fn host(&self) -> &str {
+        &self.hostname
+    }
+
+    /// Quick check: is `gh` installed and authenticated?
+    fn status(&self) -> GhStatus {
+        let bin = gh_bin();
+        if bin == "gh"
+            && Command::new("which")
+                .arg("gh")
+                .output()
+                .map(|o| !o.status.success())
+                .unwrap_or(true)
+        {
+            // If gh_bin() fell back to "gh" and `which` can't find it
+            if Command::new(bin).arg("--version").output().is_err() {
+                return GhStatus::NotInstalled;
+            }
+        }
+
+        if let Some(user) = &self.user {
+            return match Command::new(bin)
+                .args([
+                    "auth",
+                    "token",
+                    "--hostname",
+                    &self.hostname,
+                    "--user",
+                    user,
+                ])
+                .output()
+            {
+                Err(_) => GhStatus::NotInstalled,
+                Ok(output) if output.status.success() => GhStatus::Ok,
+                Ok(output) => {
+                    GhStatus::NotAuthenticated(String::from_utf8_lossy(&output.stderr).to_string())
+                }
+            };
+        }
+
+        match Command::new(bin)
+            .args(["auth", "status", "--hostname", &self.hostname])
+            .output()
+        {
+            Err(_) => GhStatus::NotInstalled,
+            Ok(output) => {
+                if output.status.success() {
+                    GhStatus::Ok
+                } else {
+                    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+                    GhStatus::NotAuthenticated(stderr)
+                }
+            }
+        }
+    }
+
+    fn login_hint(&self) -> &'static str {
+        "Run `gh auth login`."
+    }
+
+    fn graphql(&self, query: &str) -> Result<Vec<u8>> {
+        let mut cmd = Command::new(gh_bin());
+        cmd.args(["api", "graphql", "--hostname", &self.hostname])
+            .args(["-f", &format!("query={query}")]);
+        if let Some(token) = self.user_token()? {
+            cmd.env(self.token_env_var(), token);
+        }
+        let output = cmd
+            .output()
+            .context("Failed to execute `gh` CLI. Is it installed?")?;
+
+        // gh exits non-zero when the response has GraphQL `errors`, but still
+        // prints the body; hand it back so partial data isn't lost
+        if !output.status.success() && output.stdout.trim_ascii_start().starts_with(b"{") {
+            return Ok(output.stdout);
+        }
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("not logged in") || stderr.contains("authentication") {
+                bail!("gh CLI is not authenticated. Run `gh auth login` first.");
+            }
+            bail!("gh api graphql failed: {}", stderr.trim());
+        }
+
+        Ok(output.stdout)
+    }
+
+    fn rest(&self, method: &str, path: &str, headers: &[(&str, &str)]) -> Result<RestResponse> {
+        let mut cmd = Command::new(gh_bin());
+        cmd.args(["api", path, "--hostname", &self.hostname]).args([
+            "--method",
+            method,
+            "--include",
+        ]);
+        for (name, value) in headers {
+            cmd.args(["--header", &format!("{name}: {value}")]);
+        }
+        if let Some(token) = self.user_token()? {
+            cmd.env(self.token_env_var(), token);
+        }
+        let output = cmd
+            .output()
+            .context("Failed to execute `gh` CLI. Is it installed?")?;
+
+        // gh exits non-zero for anything but 2xx, 304 included; the status
+        // line says what actually happened
+        if let Some(response) = RestResponse::from_http_text(&output.stdout) {
+            if response.status >= 400 {
+                let body = String::from_utf8_lossy(&response.body);
+                bail!("GitHub API returned {}: {}", response.status, body.trim());
+            }
+            return Ok(response);
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("not logged in") || stderr.contains("authentication") {
+            bail!("gh CLI is not authenticated. Run `gh auth login` first.");
+        }
+        bail!("gh api {path} failed: {}", stderr.trim());
+    }
+
+    fn ensure_avatars(&self, authors: &[String]) {
+        cache_avatars(&self.hostname, authors, |url| {
+            let output = Command::new("curl")
+                .args(["-sL", "--max-time", "5", url])
+                .output()
+                .ok()?;
+            output.status.success().then_some(output.stdout)
+        });
+    }
+}
+
+/// Download missing GitHub avatars for the given authors. Skips already-cached ones.
+/// Downloads the image (GitHub often serves JPEG), decodes it, applies a circular
+/// mask with transparency, and saves as PNG.
+fn cache_avatars(host: &str, authors: &[String], fetch: impl Fn(&str) -> Option<Vec<u8>>) {
+    let dir = github::avatars_dir(host);
+    for author in authors {
+        let path = dir.join(format!("{author}.png"));
+        if path.exists()
+            && std::fs::metadata(&path)
+                .map(|m| m.len() > 0)
+                .unwrap_or(false)
+        {
+            continue;
+        }
+        let url = format!("https://{host}/{author}.png?size=64");
+        if let Some(bytes) = fetch(&url)
+            && let Ok(img) = image::load_from_memory(&bytes)
+        {
+            let _ = github::make_circular_png(&img, 64, &path);
+        }
+    }
+}
+
+// ── HTTPS backend ───────────────────────────────────────────────────────────
+
+/// Built-in GraphQL-over-HTTPS client authenticated with a token. Needs no
+/// `gh` install; the base URL can point at a mock server for testing.
+pub struct HttpBackend {
+    hostname: String,
+    api_base_url: String,
+    token: Option<String>,
+    agent: ureq::Agent,
+}
+
+impl HttpBackend {
+    pub fn new(hostname: &str, api_base_url: &str, token: Option<String>) -> Self {
+        Self {
+            hostname: hostname.to_string(),
+            api_base_url: api_base_url.trim_end_matches('/').to_string(),
+            token,
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(30))
+                .user_agent(concat!("ghtray/", env!("CARGO_PKG_VERSION")))
+                .build(),
+        }
+    }
+
+    fn graphql_url(&self) -> String {
+        format!("{}/graphql", self.api_base_url)
+    }
+
+    /// REST lives at the base URL on github.com and under `/v3` on GHES.
+    fn rest_url(&self, path: &str) -> String {
+        if self.hostname == DEFAULT_HOST {
+            format!("{}/{path}", self.api_base_url)
+        } else {
+            format!("{}/v3/{path}", self.api_base_url)
+        }
+    }
+}
+
+impl GitHubBackend for HttpBackend {
+    fn name(&self) -> &'static str {
+        "Token"
+    }
+
+    fn host(&self) -> &str {
+        &self.hostname
+    }
+
+    fn status(&self) -> GhStatus {
+        if self.token.is_none() {
+            return GhStatus::NotAuthenticated(
+                "No token found in GH_TOKEN, GITHUB_TOKEN, config or keyring.".to_string(),
+            );
+        }
+        match self.graphql("{ viewer { login } }") {
+            Ok(_) => GhStatus::Ok,
+            Err(e) if e.downcast_ref::<TokenRejected>().is_some() => {
+                GhStatus::NotAuthenticated(e.to_string())
+            }
+            Err(e) => GhStatus::Unreachable(format!("{e:#}")),
+        }
+    }
+
+    fn login_hint(&self) -> &'static str {
+        "Add a GitHub token in settings."
+    }
+
+    fn graphql(&self, query: &str) -> Result<Vec<u8>> {
+        let Some(token) = &self.token else {
+            bail!("No GitHub token configured. Add one in settings.");
+        };
+
+        let url = self.graphql_url();
+        let result = self
+            .agent
+            .post(&url)
+            .set("Authorization", &format!("bearer {token}"))
+            .set("Accept", "application/json")
+            .send_json(serde_json::json!({ "query": query }));
+
+        match result {
+            Ok(resp) => {
+                let body = resp
+                    .into_string()
+                    .context("Failed to read GraphQL response")?;
+                Ok(body.into_bytes())
+            }
+            Err(ureq::Error::Status(401, _)) => Err(TokenRejected.into()),
+            Err(ureq::Error::Status(code, resp)) => {
+                let body = resp.into_string().unwrap_or_default();
+                // GraphQL errors (e.g. RATE_LIMITED) can come with a 4xx; let
+                // the caller parse them
+                if body.trim_start().starts_with('{') && body.contains("\"errors\"") {
+                    return Ok(body.into_bytes());
+                }
+                bail!("GitHub API returned {code}: {}", body.trim());
+            }
+            Err(e) => Err(anyhow::Error::new(e).context("Failed to reach GitHub API")),
+        }
+    }
+
+    fn rest(&self, method: &str, path: &str, headers: &[(&str, &str)]) -> Result<RestResponse> {
+        let Some(token) = &self.token else {
+            bail!("No GitHub token configured. Add one in settings.");
+        };
+
+        let mut request = self
+            .agent
+            .request(method, &self.rest_url(path))
+            .set("Authorization", &format!("bearer {token}"))
+            .set("Accept", "application/vnd.github+json");
+        for (name, value) in headers {
+            request = request.set(name, value);
+        }
+        let result = if method == "GET" {
+            request.call()
+        } else {
+            request.send_bytes(&[])
+        };
+
+        match result {
+            Ok(resp) => {
+                let status = resp.status();
+                let headers = resp
+                    .headers_names()
+                    .into_iter()
+                    .filter_map(|name| {
+                        let value = resp.header(&name)?.to_string();
+                        Some((name, value))
+                    })
+                    .collect();
+                let mut body = Vec::new();
+                std::io::Read::read_to_end(&mut resp.into_reader(), &mut body)
+                    .context("Failed to read REST response")?;
+                Ok(RestResponse {
+                    status,
+                    headers,
+                    body,
+                })
+            }
+            Err(ureq::Error::Status(401, _)) => Err(TokenRejected.into()),
+            Err(ureq::Error::Status(code, resp)) => {
+                let body = resp.into_string().unwrap_or_default();
+                bail!("GitHub API returned {code}: {}", body.trim());
+            }
+            Err(e) => Err(anyhow::Error::new(e).context("Failed to reach GitHub API")),
+        }
+    }
+
+    fn ensure_avatars(&self, authors: &[String]) {
+        cache_avatars(&self.hostname, authors, |url| {
+            let resp = self
+                .agent
+                .get(url)
+                .timeout(Duration::from_secs(5))
+                .call()
+                .ok()?;
+            let mut bytes = Vec::new();
+            std::io::Read::read_to_end(&mut resp.into_reader(), &mut bytes).ok()?;
+            Some(bytes)
+        });
+    }
+}
+
+/// The API answered 401: the token is missing scopes, expired or revoked.
+#[derive(Debug)]
+struct TokenRejected;
+
+impl std::fmt::Display for TokenRejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("GitHub rejected the token (401). It may be expired or revoked.")
+    }
+}
+
+impl std::error::Error for TokenRejected {}
+
+/// A backend plus the tag of the account it fetches as.
+#[derive(Clone)]
+pub struct Account {
+    /// Shown on the account's PR rows; empty for the default account
+    pub tag: String,
+    pub backend: Arc<dyn GitHubBackend>,
+}
+
+impl Account {
+    pub fn untagged(backend: Arc<dyn GitHubBackend>) -> Self {
+        Self {
+            tag: String::new(),
+            backend,
+        }
+    }
+
+    /// Key for per-account state: the hostname, prefixed with the tag for
+    /// tagged accounts.
+    pub fn key(&self) -> String {
+        if self.tag.is_empty() {
+            self.backend.host().to_string()
+        } else {
+            format!("{}@{}", self.tag, self.backend.host())
+        }
+    }
+}
+
+/// Build the live backend selected by `config.auth_source` for one account.
+pub fn for_account(config: &AppConfig, account: &AccountConfig) -> Arc<dyn GitHubBackend> {
+    match config.auth_source {
+        AuthSource::GhCli => Arc::new(GhCliBackend::new(&account.host, account.login.as_deref())),
+        AuthSource::Token => {
+            let token = auth::resolve_token(config, account).map(|(token, _)| token);
+            let api_base_url = config.api_base_url_for(&config.host_config(&account.host));
+            Arc::new(HttpBackend::new(&account.host, &api_base_url, token))
+        }
+    }
+}
+
+/// Build one live backend per configured account.
+pub fn from_config(config: &AppConfig) -> Vec<Account> {
+    config
+        .effective_accounts()
+        .iter()
+        .map(|account| Account {
+            tag: account.tag.clone(),
+            backend: for_account(config, account),
+        })
+        .collect()
+}
+
+// ── Fixture backend ─────────────────────────────────────────────────────────
+
+/// Replays recorded GraphQL responses. Responses recorded with their query
+/// are served to the same query, in order; the rest are served in call
+/// order. Once a query's responses are exhausted, the last one repeats so
+/// polling settles on a stable state.
+pub struct FixtureBackend {
+    hostname: String,
+    /// Responses by `normalize_query` of the query that produced them
+    by_query: HashMap<String, Vec<Vec<u8>>>,
+    /// Responses recorded without their query
+    responses: Vec<Vec<u8>>,
+    /// Next response index per query key ("" for `responses`)
+    next: Mutex<HashMap<String, usize>>,
+}
+
+impl FixtureBackend {
+    /// Serve `responses` in call order, whatever the query.
+    pub fn new(responses: Vec<Vec<u8>>) -> Self {
+        Self {
+            hostname: DEFAULT_HOST.to_string(),
+            by_query: HashMap::new(),
+            responses,
+            next: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Serve `response` to `query`, after any earlier responses to it.
+    pub fn with_response(mut self, query: &str, response: Vec<u8>) -> Self {
+        self.by_query
+            .entry(normalize_query(query))
+            .or_default()
+            .push(response);
+        self
+    }
+
+    /// Pretend the responses came from `hostname`.
+    pub fn with_host(mut self, hostname: &str) -> Self {
+        self.hostname = hostname.to_string();
+        self
+    }
+
+    /// Load every response file in `dir`, in file name order, each with the
+    /// query saved next to it (`*.graphql`) if there is one.
+    pub fn from_dir(dir: &Path) -> Result<Self> {
+        let paths = response_files(dir)?;
+        if paths.is_empty() {
+            bail!("No fixture responses found in {}", dir.display());
+        }
+
+        let mut backend = Self::new(Vec::new());
+        for path in paths {
+            let response = std::fs::read(&path)?;
+            match std::fs::read_to_string(path.with_extension("graphql")) {
+                Ok(query) => backend = backend.with_response(&query, response),
+                Err(_) => backend.responses.push(response),
+            }
+        }
+        Ok(backend)
+    }
+}
+
+/// `*.json` response files in `dir`, sorted, leaving out the capture config.
+fn response_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read fixture dir {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter(|path| path.file_name().is_some_and(|name| name != CAPTURE_CONFIG))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Key a query for replay. The recently-merged cutoff is a date relative
+/// to today, so dates are masked; everything else (aliases, cursors, IDs)
+/// follows from the capture's config and earlier responses.
+fn normalize_query(query: &str) -> String {
+    let is_date = |s: &[u8]| {
+        s.len() >= 10
+            && s[..10].iter().enumerate().all(|(i, b)| match i {
+                4 | 7 => *b == b'-',
+                _ => b.is_ascii_digit(),
+            })
+    };
+    let mut normalized = String::with_capacity(query.len());
+    let mut i = 0;
+    while let Some(ch) = query[i..].chars().next() {
+        if is_date(&query.as_bytes()[i..]) {
+            normalized.push_str("YYYY-MM-DD");
+            i += 10;
+        } else {
+            normalized.push(ch);
+            i += ch.len_utf8();
+        }
+    }
+    normalized.trim().to_string()
+}
+
+impl GitHubBackend for FixtureBackend {
+    fn name(&self) -> &'static str {
+        "Fixture"
+    }
+
+    fn host(&self) -> &str {
+        &self.hostname
+    }
+
+    fn is_live(&self) -> bool {
+        false
+    }
+
+    fn status(&self) -> GhStatus {
+        GhStatus::Ok
+    }
+
+    fn graphql(&self, query: &str) -> Result<Vec<u8>> {
+        let key = normalize_query(query);
+        let (key, responses) = match self.by_query.get(&key) {
+            Some(responses) => (key, responses),
+            None if !self.responses.is_empty() => (String::new(), &self.responses),
+            None => bail!("No recorded response for this query:\n{query}"),
+        };
+        let mut next = self.next.lock().unwrap();
+        let next = next.entry(key).or_default();
+        let response = responses[(*next).min(responses.len() - 1)].clone();
+        *next += 1;
+        Ok(response)
+    }
+
+    fn ensure_avatars(&self, authors: &[String]) {
+        github::ensure_identicons(&self.hostname, authors);
+    }
+}
+
+/// Load a directory written by `record`: one subdirectory per account
+/// (named by `Account::key`), or the responses directly for a single
+/// untagged github.com account.
+pub fn replay(dir: &Path) -> Result<Vec<Account>> {
+    let has_responses = !response_files(dir)
+        .with_context(|| format!("Failed to read replay dir {}", dir.display()))?
+        .is_empty();
+    if has_responses {
+        return Ok(vec![Account::untagged(Arc::new(FixtureBackend::from_dir(
+            dir,
+        )?))]);
+    }
+
+    let mut subdirs: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    subdirs.sort();
+
+    let mut accounts = Vec::new();
+    for subdir in subdirs {
+        let key = subdir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let (tag, host) = key.split_once('@').unwrap_or(("", &key));
+        accounts.push(Account {
+            tag: tag.to_string(),
+            backend: Arc::new(FixtureBackend::from_dir(&subdir)?.with_host(host)),
+        });
+    }
+    if accounts.is_empty() {
+        bail!("No recorded responses found in {}", dir.display());
+    }
+    Ok(accounts)
+}
+
+/// Config file saved at the top of a capture directory.
+const CAPTURE_CONFIG: &str = "ghtray-config.json";
+
+/// Save `config`, minus tokens, with a capture. Which searches run and how
+/// their results are bucketed depend on it, so replay needs the same one.
+pub fn save_capture_config(dir: &Path, config: &AppConfig) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create record dir {}", dir.display()))?;
+    let json = serde_json::to_string_pretty(&config.without_secrets())?;
+    std::fs::write(dir.join(CAPTURE_CONFIG), json)
+        .with_context(|| format!("Failed to save capture config in {}", dir.display()))
+}
+
+/// The config a capture was recorded with, if it saved one.
+pub fn load_capture_config(dir: &Path) -> Result<Option<AppConfig>> {
+    let path = dir.join(CAPTURE_CONFIG);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json = std::fs::read_to_string(&path)?;
+    let config = serde_json::from_str(&json)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(Some(config))
+}
+
+// ── Recording backend ───────────────────────────────────────────────────────
+
+/// Passes everything through to another backend and saves every raw
+/// GraphQL response to `dir` with its query next to it, named so that file
+/// order is call order. `FixtureBackend::from_dir` plays them back.
+pub struct RecordingBackend {
+    inner: Arc<dyn GitHubBackend>,
+    dir: PathBuf,
+    seq: Mutex<u32>,
+}
+
+impl RecordingBackend {
+    pub fn new(inner: Arc<dyn GitHubBackend>, dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create record dir {}", dir.display()))?;
+        Ok(Self {
+            inner,
+            dir: dir.to_path_buf(),
+            seq: Mutex::new(0),
+        })
+    }
+}
+
+impl GitHubBackend for RecordingBackend {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn host(&self) -> &str {
+        self.inner.host()
+    }
+
+    fn is_live(&self) -> bool {
+        self.inner.is_live()
+    }
+
+    fn status(&self) -> GhStatus {
+        self.inner.status()
+    }
+
+    fn login_hint(&self) -> &'static str {
+        self.inner.login_hint()
+    }
+
+    fn graphql(&self, query: &str) -> Result<Vec<u8>> {
+        let response = self.inner.graphql(query)?;
+
+        let mut seq = self.seq.lock().unwrap();
+        *seq += 1;
+        let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ");
+        let path = self.dir.join(format!("{stamp}-{:04}.json", *seq));
+        let written = std::fs::write(&path, &response)
+            .and_then(|_| std::fs::write(path.with_extension("graphql"), query));
+        if let Err(e) = written {
+            crate::logging::log_error(&format!("Failed to record {}: {e}", path.display()));
+        }
+
+        Ok(response)
+    }
+
+    fn rest(&self, method: &str, path: &str, headers: &[(&str, &str)]) -> Result<RestResponse> {
+        self.inner.rest(method, path, headers)
+    }
+
+    fn ensure_avatars(&self, authors: &[String]) {
+        self.inner.ensure_avatars(authors);
+    }
+}
+
+/// Wrap every account's backend so its responses are saved under
+/// `dir/<account key>/`, the layout `replay` reads back, and save `config`
+/// alongside.
+pub fn record(accounts: Vec<Account>, dir: &Path, config: &AppConfig) -> Result<Vec<Account>> {
+    save_capture_config(dir, config)?;
+    accounts
+        .into_iter()
+        .map(|account| {
+            let backend = RecordingBackend::new(account.backend.clone(), &dir.join(account.key()))?;
+            Ok(Account {
+                tag: account.tag,
+                backend: Arc::new(backend),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixture_replays_in_order_then_repeats() {
+        let backend = FixtureBackend::new(vec![b"1".to_vec(), b"2".to_vec()]);
+        assert_eq!(backend.graphql("a").unwrap(), b"1");
+        assert_eq!(backend.graphql("b").unwrap(), b"2");
+        assert_eq!(backend.graphql("c").unwrap(), b"2");
+    }
+
+    #[test]
+    fn fixture_replays_by_query_ignoring_dates() {
+        let backend = FixtureBackend::new(Vec::new())
+            .with_response(
+                "{ search(query: \"merged:>2026-10-01\") }",
+                b"first".to_vec(),
+            )
+            .with_response(
+                "{ search(query: \"merged:>2026-10-01\") }",
+                b"second".to_vec(),
+            )
+            .with_response("{ viewer { login } }", b"viewer".to_vec());
+        let search = "{ search(query: \"merged:>2026-10-09\") }";
+        assert_eq!(backend.graphql(search).unwrap(), b"first");
+        assert_eq!(backend.graphql("{ viewer { login } }").unwrap(), b"viewer");
+        assert_eq!(backend.graphql(search).unwrap(), b"second");
+        // Exhausted: the last response repeats
+        assert_eq!(backend.graphql(search).unwrap(), b"second");
+        assert!(backend.graphql("{ rateLimit { cost } }").is_err());
+    }
+
+    #[test]
+    fn fixture_without_responses_fails() {
+        assert!(FixtureBackend::new(Vec::new()).graphql("a").is_err());
+    }
+
+    #[test]
+    fn parses_gh_include_output() {
+        let output = b"HTTP/2.0 200 OK\r\nLast-Modified: Thu, 15 Oct 2026 10:00:00 GMT\r\nX-Poll-Interval: 60\r\n\r\n[{\"id\":\"1\"}]";
+        let response = RestResponse::from_http_text(output).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.header("x-poll-interval"), Some("60"));
+        assert_eq!(
+            response.header("last-modified"),
+            Some("Thu, 15 Oct 2026 10:00:00 GMT")
+        );
+        assert_eq!(response.body, b"[{\"id\":\"1\"}]");
+    }
+
+    #[test]
+    fn parses_gh_include_output_with_bare_newlines() {
+        let response =
+            RestResponse::from_http_text(b"HTTP/1.1 304 Not Modified\nEtag: x\n\n").unwrap();
+        assert_eq!(response.status, 304);
+        assert_eq!(response.header("ETag"), Some("x"));
+        assert!(response.body.is_empty());
+    }
+
+    #[test]
+    fn rejects_output_without_status_line() {
+        assert!(RestResponse::from_http_text(b"{\"message\":\"Not Found\"}").is_none());
+        assert!(RestResponse::from_http_text(b"garbage\r\n\r\nbody").is_none());
+    }
+
+    #[test]
+    fn http_backend_posts_query_with_token() {
+        let (base_url, server) =
+            crate::stub_server::serve(vec![(200, r#"{"data":{"viewer":{"login":"me"}}}"#)]);
+        let backend = HttpBackend::new(DEFAULT_HOST, &base_url, Some("ghp_test".to_string()));
+
+        let body = backend.graphql("{ viewer { login } }").unwrap();
+        assert_eq!(body, br#"{"data":{"viewer":{"login":"me"}}}"#);
+
+        let request = server.join().unwrap().remove(0);
+        assert!(request.starts_with("POST /graphql "));
+        assert!(request.contains("Authorization: bearer ghp_test\r\n"));
+        assert!(request.contains(r#""query":"{ viewer { login } }""#));
+    }
+
+    #[test]
+    fn http_backend_maps_401_to_not_authenticated() {
+        let unauthorized = r#"{"message":"Bad credentials"}"#;
+        let (base_url, server) =
+            crate::stub_server::serve(vec![(401, unauthorized), (401, unauthorized)]);
+        let backend = HttpBackend::new(DEFAULT_HOST, &base_url, Some("ghp_revoked".to_string()));
+
+        let err = backend.graphql("{ viewer { login } }").unwrap_err();
+        assert!(err.downcast_ref::<TokenRejected>().is_some());
+        assert!(matches!(backend.status(), GhStatus::NotAuthenticated(_)));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn http_backend_passes_error_body_through() {
+        let (base_url, server) = crate::stub_server::serve(vec![(502, "upstream unavailable")]);
+        let backend = HttpBackend::new(DEFAULT_HOST, &base_url, Some("ghp_test".to_string()));
+
+        let err = backend.graphql("{ viewer { login } }").unwrap_err();
+        let message = format!("{err:#}");
+        assert!(message.contains("502"), "{message}");
+        assert!(message.contains("upstream unavailable"), "{message}");
+        server.join().unwrap();
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ghtray-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn replays_untagged_dir_in_file_order() {
+        let dir = temp_dir("replay-untagged");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("0002.json"), b"2").unwrap();
+        std::fs::write(dir.join("0001.json"), b"1").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"ignored").unwrap();
+
+        let accounts = replay(&dir).unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].key(), DEFAULT_HOST);
+        assert_eq!(accounts[0].backend.graphql("a").unwrap(), b"1");
+        assert_eq!(accounts[0].backend.graphql("b").unwrap(), b"2");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn recorded_accounts_replay_with_tag_and_host() {
+        let dir = temp_dir("record");
+        let live = Account {
+            tag: "work".into(),
+            backend: Arc::new(
+                FixtureBackend::new(vec![b"1".to_vec(), b"2".to_vec()]).with_host("ghe.acme.com"),
+            ),
+        };
+        let config = AppConfig {
+            github_token: Some("ghp_secret".to_string()),
+            fetch_issues: true,
+            ..AppConfig::default()
+        };
+        let recording = record(vec![live], &dir, &config).unwrap();
+        assert_eq!(recording[0].backend.graphql("a").unwrap(), b"1");
+        assert_eq!(recording[0].backend.graphql("b").unwrap(), b"2");
+
+        let saved = load_capture_config(&dir).unwrap().unwrap();
+        assert!(saved.fetch_issues);
+        assert_eq!(saved.github_token, None);
+
+        let accounts = replay(&dir).unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].tag, "work");
+        assert_eq!(accounts[0].backend.host(), "ghe.acme.com");
+        assert!(!accounts[0].backend.is_live());
+        // Served by query, not by call order
+        assert_eq!(accounts[0].backend.graphql("b").unwrap(), b"2");
+        assert_eq!(accounts[0].backend.graphql("a").unwrap(), b"1");
+        assert!(accounts[0].backend.graphql("c").is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,11 +1,266 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 
-use crate::models::Bucket;
+use crate::models::{Bucket, Label};
 use crate::state::data_dir;
 
+/// How GH Tray authenticates against GitHub.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthSource {
+    /// Shell out to `gh api graphql` (uses the `gh auth` session)
+    #[default]
+    GhCli,
+    /// Built-in HTTPS client with a token from env, config or keyring
+    Token,
+}
+
+/// What to do with bot-authored PRs in a bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotPrHandling {
+    /// List them like any other PR
+    #[default]
+    Show,
+    /// Leave them out of the menu and the badge
+    Hide,
+    /// Replace them with a single "N bot PRs" row
+    Collapse,
+    /// List them, but don't count them in the badge
+    NoBadge,
+}
+
+/// What to do with PRs that haven't been updated in a while.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StalePrHandling {
+    /// Move them to the collapsed Stale section
+    #[default]
+    Collapse,
+    /// Leave them out entirely
+    Hide,
+}
+
+/// How the team view groups its PRs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TeamGrouping {
+    /// One group per author
+    #[default]
+    Author,
+    /// One group per lifecycle bucket
+    Bucket,
+}
+
+/// Section ID of team view rows. They never count in the badge.
+pub const TEAM_SECTION_ID: &str = "team";
+
+/// What a label rule does to PRs carrying the label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelAction {
+    /// Leave the PR out entirely
+    Hide,
+    /// Count it in the badge; once any badge rule exists, only matching PRs count
+    Badge,
+    /// Put `prefix` in front of the row's title
+    Prefix,
+}
+
+/// A rule keyed on a PR label name (case-insensitive).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelRule {
+    pub label: String,
+    pub action: LabelAction,
+    /// Text shown before the title, for `Prefix` rules (e.g. "🔥")
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub prefix: String,
+}
+
+impl LabelRule {
+    fn matches(&self, labels: &[Label]) -> bool {
+        labels
+            .iter()
+            .any(|l| l.name.eq_ignore_ascii_case(&self.label))
+    }
+}
+
+/// A named GitHub search shown as its own menu section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    /// GitHub search syntax, e.g. "is:open repo:acme/infra label:deploy"
+    pub query: String,
+}
+
+impl SavedSearch {
+    /// Section ID used for ordering, visibility and badge settings. Case
+    /// doesn't matter; ASCII letters and digits are kept, spaces become `_`
+    /// and anything else its hex code point (`-21-` for "!"), so distinct
+    /// names in any script get distinct IDs.
+    pub fn id(&self) -> String {
+        let mut id = String::from("search_");
+        for c in self.name.to_lowercase().chars() {
+            match c {
+                c if c.is_ascii_alphanumeric() => id.push(c),
+                ' ' => id.push('_'),
+                c => id.push_str(&format!("-{:x}-", c as u32)),
+            }
+        }
+        id
+    }
+
+    /// The ID before non-ASCII names got distinct IDs: every character other
+    /// than a letter or digit became `_`.
+    fn legacy_id(&self) -> String {
+        let slug: String = self
+            .name
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        format!("search_{slug}")
+    }
+
+    /// The query asks for issues, which a saved search can't show.
+    pub fn searches_issues(&self) -> bool {
+        self.query
+            .split_whitespace()
+            .any(|token| token == "is:issue" || token == "type:issue")
+    }
+
+    /// The query as sent to GitHub; only PRs can be shown, so `is:pr` is
+    /// added when the query doesn't restrict the type itself.
+    pub fn search_query(&self) -> String {
+        let query = self.query.trim();
+        let restricts_type = query
+            .split_whitespace()
+            .any(|token| matches!(token, "is:pr" | "type:pr" | "is:issue" | "type:issue"));
+        if restricts_type {
+            query.to_string()
+        } else {
+            format!("is:pr {query}")
+        }
+    }
+}
+
+/// The first two saved searches whose names give the same section ID
+/// ("Deploy" and "deploy"); they would share one section and its settings.
+pub fn colliding_saved_searches(searches: &[SavedSearch]) -> Option<(&SavedSearch, &SavedSearch)> {
+    searches.iter().enumerate().find_map(|(i, later)| {
+        searches[..i]
+            .iter()
+            .find(|earlier| earlier.id() == later.id())
+            .map(|earlier| (earlier, later))
+    })
+}
+
+/// A menu section: a built-in bucket or a saved search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Section {
+    Bucket(Bucket),
+    Search(SavedSearch),
+}
+
+impl Section {
+    pub fn id(&self) -> String {
+        match self {
+            Section::Bucket(bucket) => bucket.id().to_string(),
+            Section::Search(search) => search.id(),
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            Section::Bucket(bucket) => bucket.label(),
+            Section::Search(search) => &search.name,
+        }
+    }
+}
+
+/// Host used when no `hosts` are configured.
+pub const DEFAULT_HOST: &str = "github.com";
+
+/// A GitHub instance to fetch from (github.com or a GitHub Enterprise Server).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostConfig {
+    /// Hostname as passed to `gh --hostname` (e.g. "ghe.acme.com")
+    pub hostname: String,
+    /// API base URL for the token client (defaults to `https://{hostname}/api`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_base_url: Option<String>,
+    /// Token for this host (checked after GH_ENTERPRISE_TOKEN / GITHUB_ENTERPRISE_TOKEN)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// OAuth App client ID for device-flow sign-in on a GHES host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oauth_client_id: Option<String>,
+}
+
+impl HostConfig {
+    pub fn new(hostname: &str) -> Self {
+        Self {
+            hostname: hostname.to_string(),
+            api_base_url: None,
+            token: None,
+            oauth_client_id: None,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.hostname == DEFAULT_HOST
+    }
+}
+
+/// A GitHub account to fetch PRs for. Each account runs its own searches;
+/// the results are merged into one menu.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountConfig {
+    /// Short tag shown on the account's PR rows ("work", "oss")
+    pub tag: String,
+    /// Host the account lives on
+    #[serde(default = "default_host")]
+    pub host: String,
+    /// gh login to fetch as; the active gh account if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub login: Option<String>,
+    /// Token for this account when using the token client
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Count this account's PRs in the tray badge
+    #[serde(default = "default_true")]
+    pub badge: bool,
+    /// Send notifications for this account's PRs
+    #[serde(default = "default_true")]
+    pub notifications: bool,
+}
+
+impl AccountConfig {
+    /// The untagged account used when none are configured: whoever is
+    /// signed in on `host`.
+    pub fn for_host(host: &str) -> Self {
+        Self {
+            tag: String::new(),
+            host: host.to_string(),
+            login: None,
+            token: None,
+            badge: true,
+            notifications: true,
+        }
+    }
+
+    /// OS keyring entry for this account's token: the hostname, or
+    /// "login@hostname" for a specific login.
+    pub fn keyring_user(&self) -> String {
+        match &self.login {
+            Some(login) => format!("{login}@{}", self.host),
+            None => self.host.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     /// Poll interval in seconds (minimum 30)
@@ -26,19 +281,133 @@ pub struct AppConfig {
     /// Bucket IDs that count towards the badge number
     #[serde(default = "default_badge_buckets")]
     pub badge_buckets: HashSet<String>,
+    /// Schema version the config was saved with (missing = before versioning)
+    #[serde(default)]
+    pub config_version: u32,
     /// Custom display order for buckets (list of bucket IDs)
     #[serde(default)]
     pub bucket_order: Vec<String>,
+    /// Maximum PRs fetched per search before pagination stops
+    #[serde(default = "default_max_results_per_search")]
+    pub max_results_per_search: u32,
+    /// Below this many remaining API points, polling slows down
+    #[serde(default = "default_rate_limit_threshold")]
+    pub rate_limit_threshold: u32,
+    /// Whether to use the gh CLI or the built-in token client
+    #[serde(default)]
+    pub auth_source: AuthSource,
+    /// Personal access token (checked after GH_TOKEN / GITHUB_TOKEN)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_token: Option<String>,
+    /// API base URL for the token client (GraphQL endpoint is `{base}/graphql`)
+    #[serde(default = "default_api_base_url")]
+    pub api_base_url: String,
+    /// OAuth App client ID for device-flow sign-in (overrides the built-in one)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oauth_client_id: Option<String>,
+    /// Device authorization endpoint for sign-in
+    #[serde(default = "default_oauth_device_code_url")]
+    pub oauth_device_code_url: String,
+    /// Access token endpoint polled during sign-in
+    #[serde(default = "default_oauth_access_token_url")]
+    pub oauth_access_token_url: String,
+    /// GitHub hosts to fetch from — empty means github.com only
+    #[serde(default)]
+    pub hosts: Vec<HostConfig>,
+    /// Accounts to fetch for — empty means the signed-in account on each host
+    #[serde(default)]
+    pub accounts: Vec<AccountConfig>,
+    /// Bot logins or glob patterns ("*-bot"); logins ending in `[bot]` always match
+    #[serde(default = "default_bot_logins")]
+    pub bot_logins: Vec<String>,
+    /// What to do with bot-authored PRs, per bucket ID (missing = show)
+    #[serde(default)]
+    pub bot_prs: HashMap<String, BotPrHandling>,
+    /// PRs not updated for this many days are stale (unset = never)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale_after_days: Option<u32>,
+    /// Whether stale PRs are collapsed or hidden
+    #[serde(default)]
+    pub stale_prs: StalePrHandling,
+    /// Per-repo staleness threshold ("owner/name" → days, 0 = never stale)
+    #[serde(default)]
+    pub repo_stale_after_days: HashMap<String, u32>,
+    /// Label-based hide / badge / prefix rules, applied in order
+    #[serde(default)]
+    pub label_rules: Vec<LabelRule>,
+    /// Extra searches fetched alongside the built-in ones
+    #[serde(default)]
+    pub saved_searches: Vec<SavedSearch>,
+    /// Also fetch open issues assigned to or mentioning the viewer
+    #[serde(default)]
+    pub fetch_issues: bool,
+    /// Also list unread threads from the GitHub notifications inbox
+    #[serde(default)]
+    pub fetch_notifications: bool,
+    /// Team view: list open PRs by this team's members ("org/team-slug").
+    /// Fetched with the first account only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team_slug: Option<String>,
+    /// Team view: more logins whose open PRs are listed
+    #[serde(default)]
+    pub team_members: Vec<String>,
+    #[serde(default)]
+    pub team_group_by: TeamGrouping,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_host() -> String {
+    DEFAULT_HOST.to_string()
+}
+
+fn default_max_results_per_search() -> u32 {
+    200
+}
+
+fn default_rate_limit_threshold() -> u32 {
+    500
+}
+
+fn default_api_base_url() -> String {
+    "https://api.github.com".to_string()
+}
+
+fn default_oauth_device_code_url() -> String {
+    "https://github.com/login/device/code".to_string()
+}
+
+fn default_oauth_access_token_url() -> String {
+    "https://github.com/login/oauth/access_token".to_string()
+}
+
+fn default_bot_logins() -> Vec<String> {
+    [
+        "dependabot",
+        "renovate",
+        "github-actions",
+        "cursor",
+        "graphite-app",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+/// Bumped whenever `AppConfig::migrated` gains a step.
+const CONFIG_VERSION: u32 = 2;
+
+/// Badged-by-default buckets added after configs were first saved, with the
+/// config version that introduced them. Older configs get them on load.
+const ADDED_BADGE_BUCKETS: &[(u32, &str)] = &[(1, "ci_failing"), (1, "updated_since_your_review")];
+
 fn default_badge_buckets() -> HashSet<String> {
     HashSet::from([
         "needs_your_review".to_string(),
         "returned_to_you".to_string(),
+        "ci_failing".to_string(),
+        "updated_since_your_review".to_string(),
     ])
 }
 
@@ -52,7 +421,30 @@ impl Default for AppConfig {
             notification_sound: true,
             hidden_buckets: HashSet::new(),
             badge_buckets: default_badge_buckets(),
+            config_version: CONFIG_VERSION,
             bucket_order: Vec::new(),
+            max_results_per_search: default_max_results_per_search(),
+            rate_limit_threshold: default_rate_limit_threshold(),
+            auth_source: AuthSource::default(),
+            github_token: None,
+            api_base_url: default_api_base_url(),
+            oauth_client_id: None,
+            oauth_device_code_url: default_oauth_device_code_url(),
+            oauth_access_token_url: default_oauth_access_token_url(),
+            hosts: Vec::new(),
+            accounts: Vec::new(),
+            bot_logins: default_bot_logins(),
+            bot_prs: HashMap::new(),
+            stale_after_days: None,
+            stale_prs: StalePrHandling::default(),
+            repo_stale_after_days: HashMap::new(),
+            label_rules: Vec::new(),
+            saved_searches: Vec::new(),
+            fetch_issues: false,
+            fetch_notifications: false,
+            team_slug: None,
+            team_members: Vec::new(),
+            team_group_by: TeamGrouping::default(),
         }
     }
 }
@@ -67,13 +459,42 @@ impl AppConfig {
         if path.exists() {
             fs::read_to_string(&path)
                 .ok()
-                .and_then(|s| serde_json::from_str(&s).ok())
+                .and_then(|s| serde_json::from_str::<Self>(&s).ok())
+                .map(Self::migrated)
                 .unwrap_or_default()
         } else {
             Self::default()
         }
     }
 
+    /// Brings a config saved by an older version up to date: buckets that
+    /// became badged by default since then start counting, and saved
+    /// searches keep their settings under their current IDs.
+    fn migrated(mut self) -> Self {
+        for (version, bucket) in ADDED_BADGE_BUCKETS {
+            if self.config_version < *version {
+                self.badge_buckets.insert(bucket.to_string());
+            }
+        }
+        if self.config_version < 2 {
+            for search in &self.saved_searches {
+                let (old, new) = (search.legacy_id(), search.id());
+                for ids in [&mut self.hidden_buckets, &mut self.badge_buckets] {
+                    if ids.remove(&old) {
+                        ids.insert(new.clone());
+                    }
+                }
+                for id in &mut self.bucket_order {
+                    if *id == old {
+                        id.clone_from(&new);
+                    }
+                }
+            }
+        }
+        self.config_version = CONFIG_VERSION;
+        self
+    }
+
     pub fn save(&self) -> Result<()> {
         let path = Self::config_path();
         let json = serde_json::to_string_pretty(self)?;
@@ -81,6 +502,75 @@ impl AppConfig {
         Ok(())
     }
 
+    /// A copy with every token removed, safe to share.
+    pub fn without_secrets(&self) -> AppConfig {
+        let mut config = self.clone();
+        config.github_token = None;
+        for host in &mut config.hosts {
+            host.token = None;
+        }
+        for account in &mut config.accounts {
+            account.token = None;
+        }
+        config
+    }
+
+    /// Hosts to fetch from, falling back to github.com.
+    pub fn effective_hosts(&self) -> Vec<HostConfig> {
+        if self.hosts.is_empty() {
+            vec![HostConfig::new(DEFAULT_HOST)]
+        } else {
+            self.hosts.clone()
+        }
+    }
+
+    /// Settings for `hostname`, whether or not it's listed in `hosts`.
+    pub fn host_config(&self, hostname: &str) -> HostConfig {
+        self.hosts
+            .iter()
+            .find(|h| h.hostname == hostname)
+            .cloned()
+            .unwrap_or_else(|| HostConfig::new(hostname))
+    }
+
+    /// Accounts to fetch for, falling back to one untagged account per host.
+    pub fn effective_accounts(&self) -> Vec<AccountConfig> {
+        if self.accounts.is_empty() {
+            self.effective_hosts()
+                .iter()
+                .map(|host| AccountConfig::for_host(&host.hostname))
+                .collect()
+        } else {
+            self.accounts.clone()
+        }
+    }
+
+    fn account(&self, tag: &str) -> Option<&AccountConfig> {
+        self.accounts.iter().find(|a| a.tag == tag)
+    }
+
+    pub fn account_counts_for_badge(&self, tag: &str) -> bool {
+        self.account(tag).is_none_or(|a| a.badge)
+    }
+
+    pub fn account_notifies(&self, tag: &str) -> bool {
+        self.account(tag).is_none_or(|a| a.notifications)
+    }
+
+    /// API base URL for the token client on `host`.
+    pub fn api_base_url_for(&self, host: &HostConfig) -> String {
+        match &host.api_base_url {
+            Some(url) => url.clone(),
+            None if host.is_default() => self.api_base_url.clone(),
+            None => format!("https://{}/api", host.hostname),
+        }
+    }
+
+    /// Whether a team slug or team logins are configured.
+    pub fn has_team_view(&self) -> bool {
+        self.team_slug.is_some() || !self.team_members.is_empty()
+    }
+
     pub fn is_repo_allowed(&self, repo: &str) -> bool {
         !self.blocked_repos.contains(repo)
     }
@@ -97,25 +587,353 @@ impl AppConfig {
         self.badge_buckets.contains(bucket_id)
     }
 
-    /// Returns the bucket display order. Uses custom order if set, otherwise default.
-    pub fn ordered_buckets(&self) -> Vec<Bucket> {
-        if self.bucket_order.is_empty() {
-            return Bucket::display_order().to_vec();
-        }
+    fn label_rules(&self, action: LabelAction) -> impl Iterator<Item = &LabelRule> {
+        self.label_rules.iter().filter(move |r| r.action == action)
+    }
+
+    pub fn label_hidden(&self, labels: &[Label]) -> bool {
+        self.label_rules(LabelAction::Hide)
+            .any(|r| r.matches(labels))
+    }
+
+    /// Whether a PR with `labels` may count in the badge: always, unless
+    /// badge rules exist and none of them match.
+    pub fn label_counts_for_badge(&self, labels: &[Label]) -> bool {
+        let mut rules = self.label_rules(LabelAction::Badge).peekable();
+        rules.peek().is_none() || rules.any(|r| r.matches(labels))
+    }
+
+    /// Prefix from the first matching prefix rule.
+    pub fn label_prefix(&self, labels: &[Label]) -> Option<&str> {
+        self.label_rules(LabelAction::Prefix)
+            .find(|r| r.matches(labels))
+            .map(|r| r.prefix.as_str())
+            .filter(|prefix| !prefix.is_empty())
+    }
+
+    /// Staleness threshold for `repo`, if PRs there can go stale at all.
+    pub fn stale_after_days_for(&self, repo: &str) -> Option<u32> {
+        self.repo_stale_after_days
+            .get(repo)
+            .copied()
+            .or(self.stale_after_days)
+            .filter(|days| *days > 0)
+    }
 
-        let mut ordered: Vec<Bucket> = self
+    pub fn is_bot(&self, login: &str) -> bool {
+        login.ends_with("[bot]")
+            || self
+                .bot_logins
+                .iter()
+                .any(|pattern| glob_match(&pattern.to_lowercase(), &login.to_lowercase()))
+    }
+
+    pub fn bot_handling(&self, bucket_id: &str) -> BotPrHandling {
+        self.bot_prs.get(bucket_id).copied().unwrap_or_default()
+    }
+
+    /// Whether a PR by `author` in `bucket_id` may count in the badge, as far
+    /// as bot handling is concerned.
+    pub fn bot_counts_for_badge(&self, bucket_id: &str, author: &str) -> bool {
+        !matches!(
+            self.bot_handling(bucket_id),
+            BotPrHandling::Hide | BotPrHandling::NoBadge
+        ) || !self.is_bot(author)
+    }
+
+    /// Returns the section display order: built-in buckets and saved
+    /// searches. Uses custom order if set, otherwise default.
+    pub fn ordered_sections(&self) -> Vec<Section> {
+        let all: Vec<Section> = Bucket::display_order()
+            .iter()
+            .map(|b| Section::Bucket(*b))
+            .chain(self.saved_searches.iter().cloned().map(Section::Search))
+            .collect();
+
+        let mut ordered: Vec<Section> = self
             .bucket_order
             .iter()
-            .filter_map(|id| Bucket::from_id(id))
+            .filter_map(|id| all.iter().find(|s| s.id() == *id).cloned())
             .collect();
 
-        // Append any missing buckets (e.g. newly added ones)
-        for b in Bucket::display_order() {
-            if !ordered.contains(b) {
-                ordered.push(*b);
+        // Append any missing sections (e.g. newly added ones)
+        for section in all {
+            if !ordered.contains(&section) {
+                ordered.push(section);
             }
         }
 
         ordered
     }
 }
+
+/// Match `text` against `pattern`, where `*` matches any run of characters.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let Some((head, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut remaining) = text.strip_prefix(head) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let tail = parts.pop().unwrap_or_default();
+    for part in parts {
+        match remaining.find(part) {
+            Some(at) => remaining = &remaining[at + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.len() >= tail.len() && remaining.ends_with(tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn old_configs_are_migrated_once() {
+        let old: AppConfig = serde_json::from_str(
+            r#"{"poll_interval_secs": 120, "merged_window_days": 7, "blocked_repos": [],
+                "badge_buckets": ["needs_your_review"]}"#,
+        )
+        .unwrap();
+        let migrated = old.migrated();
+        assert_eq!(
+            migrated.badge_buckets,
+            HashSet::from([
+                "needs_your_review".to_string(),
+                "ci_failing".to_string(),
+                "updated_since_your_review".to_string(),
+            ])
+        );
+        assert_eq!(migrated.config_version, CONFIG_VERSION);
+
+        // Saved search settings follow the search to its new ID
+        let old: AppConfig = serde_json::from_str(
+            r#"{"poll_interval_secs": 120, "merged_window_days": 7, "blocked_repos": [],
+                "config_version": 1, "badge_buckets": ["search_on_call"],
+                "bucket_order": ["search_on_call", "drafts"],
+                "saved_searches": [{"name": "On-call", "query": "label:incident"}]}"#,
+        )
+        .unwrap();
+        let migrated_search = old.migrated();
+        assert_eq!(
+            migrated_search.badge_buckets,
+            HashSet::from(["search_on-2d-call".to_string()])
+        );
+        assert_eq!(
+            migrated_search.bucket_order,
+            ["search_on-2d-call", "drafts"]
+        );
+
+        // Once current, a bucket the user unticked stays unticked
+        let mut current = migrated;
+        current.badge_buckets.remove("ci_failing");
+        let json = serde_json::to_string(&current).unwrap();
+        let reloaded = serde_json::from_str::<AppConfig>(&json).unwrap().migrated();
+        assert!(!reloaded.badge_buckets.contains("ci_failing"));
+    }
+
+    #[test]
+    fn api_base_url_per_host() {
+        let config = AppConfig::default();
+        assert_eq!(
+            config.api_base_url_for(&HostConfig::new(DEFAULT_HOST)),
+            config.api_base_url
+        );
+        assert_eq!(
+            config.api_base_url_for(&HostConfig::new("ghe.acme.com")),
+            "https://ghe.acme.com/api"
+        );
+        let custom = HostConfig {
+            api_base_url: Some("https://api.ghe.acme.com".to_string()),
+            ..HostConfig::new("ghe.acme.com")
+        };
+        assert_eq!(config.api_base_url_for(&custom), "https://api.ghe.acme.com");
+    }
+
+    #[test]
+    fn effective_accounts_default_to_one_per_host() {
+        let config = AppConfig {
+            hosts: vec![
+                HostConfig::new(DEFAULT_HOST),
+                HostConfig::new("ghe.acme.com"),
+            ],
+            ..AppConfig::default()
+        };
+        let hosts: Vec<String> = config
+            .effective_accounts()
+            .into_iter()
+            .map(|account| account.host)
+            .collect();
+        assert_eq!(hosts, [DEFAULT_HOST, "ghe.acme.com"]);
+    }
+
+    #[test]
+    fn account_flags_default_to_on() {
+        let config = AppConfig {
+            accounts: vec![AccountConfig {
+                tag: "oss".to_string(),
+                badge: false,
+                ..AccountConfig::for_host(DEFAULT_HOST)
+            }],
+            ..AppConfig::default()
+        };
+        assert!(!config.account_counts_for_badge("oss"));
+        assert!(config.account_notifies("oss"));
+        // PRs from accounts no longer configured still count
+        assert!(config.account_counts_for_badge("gone"));
+    }
+
+    #[test]
+    fn effective_hosts_default_to_github_com() {
+        let hosts = AppConfig::default().effective_hosts();
+        assert_eq!(hosts.len(), 1);
+        assert!(hosts[0].is_default());
+    }
+
+    #[test]
+    fn glob_match_wildcards() {
+        assert!(glob_match("dependabot", "dependabot"));
+        assert!(!glob_match("dependabot", "dependabot2"));
+        assert!(glob_match("*-bot", "deploy-bot"));
+        assert!(glob_match("ci-*", "ci-runner"));
+        assert!(glob_match("svc-*-bot", "svc-deploy-bot"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "abc"));
+        assert!(!glob_match("a*b*c", "acb"));
+        // The tail can't reuse what a middle part matched
+        assert!(!glob_match("*ab*b", "ab"));
+    }
+
+    #[test]
+    fn is_bot_matches_patterns_case_insensitively() {
+        let config = AppConfig {
+            bot_logins: vec!["Deploy-*".to_string()],
+            ..AppConfig::default()
+        };
+        assert!(config.is_bot("renovate[bot]"));
+        assert!(config.is_bot("deploy-prod"));
+        assert!(!config.is_bot("alice"));
+    }
+
+    #[test]
+    fn bot_handling_decides_badge_counts() {
+        let config = AppConfig {
+            bot_prs: HashMap::from([
+                ("needs_your_review".to_string(), BotPrHandling::NoBadge),
+                ("approved".to_string(), BotPrHandling::Collapse),
+            ]),
+            ..AppConfig::default()
+        };
+        assert!(!config.bot_counts_for_badge("needs_your_review", "dependabot"));
+        assert!(config.bot_counts_for_badge("needs_your_review", "alice"));
+        assert!(config.bot_counts_for_badge("approved", "dependabot"));
+        assert_eq!(config.bot_handling("drafts"), BotPrHandling::Show);
+    }
+
+    #[test]
+    fn label_rules_hide_badge_and_prefix() {
+        let rule = |label: &str, action, prefix: &str| LabelRule {
+            label: label.to_string(),
+            action,
+            prefix: prefix.to_string(),
+        };
+        let labels = |names: &[&str]| -> Vec<Label> {
+            names
+                .iter()
+                .map(|name| Label {
+                    name: name.to_string(),
+                    color: String::new(),
+                })
+                .collect()
+        };
+        let mut config = AppConfig::default();
+        assert!(config.label_counts_for_badge(&labels(&[])));
+
+        config.label_rules = vec![
+            rule("wip", LabelAction::Hide, ""),
+            rule("P0", LabelAction::Badge, ""),
+            rule("p0", LabelAction::Prefix, "🔥"),
+            rule("urgent", LabelAction::Prefix, "⚡"),
+        ];
+        assert!(config.label_hidden(&labels(&["WIP"])));
+        assert!(!config.label_hidden(&labels(&["bug"])));
+        assert!(config.label_counts_for_badge(&labels(&["bug", "p0"])));
+        assert!(!config.label_counts_for_badge(&labels(&["bug"])));
+        assert_eq!(config.label_prefix(&labels(&["urgent", "P0"])), Some("🔥"));
+        assert_eq!(config.label_prefix(&labels(&["bug"])), None);
+    }
+
+    #[test]
+    fn saved_searches_with_same_slug_collide() {
+        let search = |name: &str| SavedSearch {
+            name: name.to_string(),
+            query: "label:bug".to_string(),
+        };
+        let searches = [search("Deploy!"), search("on-call"), search("deploy!")];
+        let (earlier, later) = colliding_saved_searches(&searches).unwrap();
+        assert_eq!(
+            (earlier.name.as_str(), later.name.as_str()),
+            ("Deploy!", "deploy!")
+        );
+        assert!(colliding_saved_searches(&searches[..2]).is_none());
+
+        let distinct = [
+            search("レビュー"),
+            search("テスト"),
+            search("Deploy?"),
+            search("on call"),
+            search("on_call"),
+        ];
+        assert!(colliding_saved_searches(&distinct).is_none());
+        assert_eq!(search("Deploy!").id(), "search_deploy-21-");
+        assert_eq!(search("on call").id(), "search_on_call");
+    }
+
+    #[test]
+    fn saved_search_query_adds_pr_qualifier() {
+        let search = |query: &str| SavedSearch {
+            name: "Test".to_string(),
+            query: query.to_string(),
+        };
+        assert_eq!(search("label:bug").search_query(), "is:pr label:bug");
+        assert_eq!(
+            search(" is:pr label:bug ").search_query(),
+            "is:pr label:bug"
+        );
+        assert_eq!(
+            search("type:pr repo:acme/app").search_query(),
+            "type:pr repo:acme/app"
+        );
+        assert_eq!(
+            search("is:private label:bug").search_query(),
+            "is:pr is:private label:bug"
+        );
+
+        let issues = search("is:issue label:bug");
+        assert_eq!(issues.search_query(), "is:issue label:bug");
+        assert!(issues.searches_issues());
+        assert!(!search("is:pr label:issue").searches_issues());
+    }
+
+    #[test]
+    fn ordered_sections_include_saved_searches() {
+        let deploys = SavedSearch {
+            name: "Infra deploys".to_string(),
+            query: "repo:acme/infra label:deploy".to_string(),
+        };
+        assert_eq!(deploys.id(), "search_infra_deploys");
+
+        let config = AppConfig {
+            saved_searches: vec![deploys.clone()],
+            bucket_order: vec!["search_infra_deploys".to_string(), "drafts".to_string()],
+            ..AppConfig::default()
+        };
+        let sections = config.ordered_sections();
+        assert_eq!(sections[0], Section::Search(deploys));
+        assert_eq!(sections[0].label(), "Infra deploys");
+        assert_eq!(sections[1], Section::Bucket(Bucket::Drafts));
+        assert_eq!(sections.len(), Bucket::display_order().len() + 1);
+    }
+}
@@ -0,0 +1,408 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use serde_json::{Value, json};
+
+use crate::backend::{GitHubBackend, RestResponse};
+use crate::config::DEFAULT_HOST;
+use crate::github::{self, GhStatus};
+
+pub const DEMO_VIEWER: &str = "demo-user";
+
+/// The demo inbox never changes, so every conditional request gets a 304.
+const DEMO_LAST_MODIFIED: &str = "Thu, 01 Jan 2026 00:00:00 GMT";
+
+/// Serves canned GraphQL responses so the app can be shown off (and the
+/// categorization pipeline exercised) without a GitHub account.
+pub struct DemoBackend;
+
+impl GitHubBackend for DemoBackend {
+    fn name(&self) -> &'static str {
+        "Demo"
+    }
+
+    fn is_live(&self) -> bool {
+        false
+    }
+
+    fn status(&self) -> GhStatus {
+        GhStatus::Ok
+    }
+
+    fn graphql(&self, query: &str) -> Result<Vec<u8>> {
+        let response = if query.contains("search(") {
+            let mut response = demo_search_response();
+            if query.contains("assignedIssues") {
+                response["data"]["assignedIssues"] = search(vec![issue(
+                    "i1",
+                    77,
+                    "Checkout fails on Safari 17",
+                    "acme/web-app",
+                    "sarah-ui",
+                    30,
+                )]);
+                response["data"]["mentionedIssues"] = search(vec![issue(
+                    "i2",
+                    1203,
+                    "Postgres 16 rollout plan",
+                    "acme/infra",
+                    "james-eng",
+                    50,
+                )]);
+            }
+            if query.contains("team0:") {
+                response["data"]["team0"] = demo_team_prs();
+            }
+            response
+        } else if query.contains("team(slug:") {
+            json!({ "data": { "organization": { "team": { "members": { "nodes": [
+                { "login": "olivia-dev" }, { "login": "james-eng" }, { "login": "sarah-ui" },
+            ] } } } } })
+        } else if query.contains("organizations") {
+            json!({ "data": { "viewer": { "organizations": { "nodes": [
+                { "teams": { "nodes": [{ "combinedSlug": "acme/platform" }] } },
+            ] } } } })
+        } else {
+            json!({ "data": { "viewer": { "login": DEMO_VIEWER } } })
+        };
+        Ok(serde_json::to_vec(&response)?)
+    }
+
+    fn rest(&self, method: &str, path: &str, headers: &[(&str, &str)]) -> Result<RestResponse> {
+        // Marking as read answers 205 Reset Content
+        if method != "GET" || !path.starts_with("notifications") {
+            return Ok(RestResponse {
+                status: 205,
+                ..Default::default()
+            });
+        }
+        let modified_since = headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case("If-Modified-Since"));
+        Ok(RestResponse {
+            status: if modified_since { 304 } else { 200 },
+            headers: vec![
+                ("Last-Modified".to_string(), DEMO_LAST_MODIFIED.to_string()),
+                ("X-Poll-Interval".to_string(), "60".to_string()),
+            ],
+            body: if modified_since {
+                Vec::new()
+            } else {
+                serde_json::to_vec(&demo_notifications())?
+            },
+        })
+    }
+
+    fn ensure_avatars(&self, authors: &[String]) {
+        github::ensure_identicons(DEFAULT_HOST, authors);
+    }
+}
+
+// ── Demo data ────────────────────────────────────────────────────────────────
+
+struct DemoPr<'a> {
+    id: &'a str,
+    number: u32,
+    title: &'a str,
+    repo: &'a str,
+    author: &'a str,
+    ci: Option<&'a str>,
+    hours_ago: i64,
+}
+
+impl DemoPr<'_> {
+    fn to_json(&self) -> Value {
+        let now = Utc::now();
+        let updated = now - Duration::hours(self.hours_ago / 2);
+        json!({
+            "id": self.id,
+            "number": self.number,
+            "title": self.title,
+            "url": format!("https://github.com/{}/pull/{}", self.repo, self.number),
+            "isDraft": false,
+            "createdAt": now - Duration::hours(self.hours_ago),
+            "updatedAt": updated,
+            "repository": { "nameWithOwner": self.repo, "defaultBranchRef": { "name": "main" } },
+            "author": { "login": self.author },
+            "reviewDecision": null,
+            "latestReviews": { "nodes": [] },
+            "reviewRequests": { "nodes": [] },
+            "mergeable": "MERGEABLE",
+            "mergeStateStatus": "CLEAN",
+            "baseRefName": "main",
+            "headRefName": format!("{}-{}", self.author, self.number),
+            "isCrossRepository": false,
+            "reviewThreads": { "totalCount": 0, "nodes": [] },
+            "additions": self.number * 7 % 640,
+            "deletions": self.number * 3 % 210,
+            "changedFiles": self.number % 23 + 1,
+            "labels": { "nodes": [] },
+            "commits": { "nodes": [{ "commit": {
+                "oid": format!("abc{}", self.id),
+                "committedDate": updated,
+                "statusCheckRollup": self.ci.map(|state| json!({
+                    "state": state,
+                    "contexts": { "nodes": self.failing_check(state) },
+                })),
+            } }] },
+        })
+    }
+
+    fn failing_check(&self, state: &str) -> Vec<Value> {
+        if state != "FAILURE" {
+            return Vec::new();
+        }
+        vec![json!({
+            "name": "test (ubuntu-latest)",
+            "conclusion": "FAILURE",
+            "detailsUrl": format!("https://github.com/{}/actions/runs/{}", self.repo, self.number),
+        })]
+    }
+
+    fn with(&self, fields: Value) -> Value {
+        let mut pr = self.to_json();
+        if let (Some(pr), Value::Object(fields)) = (pr.as_object_mut(), fields) {
+            pr.extend(fields);
+        }
+        pr
+    }
+
+    fn merged(&self) -> Value {
+        let now = Utc::now();
+        json!({
+            "id": self.id,
+            "number": self.number,
+            "title": self.title,
+            "url": format!("https://github.com/{}/pull/{}", self.repo, self.number),
+            "createdAt": now - Duration::hours(self.hours_ago),
+            "mergedAt": now - Duration::hours(self.hours_ago / 2),
+            "repository": { "nameWithOwner": self.repo },
+            "author": { "login": self.author },
+        })
+    }
+}
+
+fn issue(id: &str, number: u32, title: &str, repo: &str, author: &str, hours_ago: i64) -> Value {
+    let now = Utc::now();
+    json!({
+        "id": id,
+        "number": number,
+        "title": title,
+        "url": format!("https://github.com/{repo}/issues/{number}"),
+        "createdAt": now - Duration::hours(hours_ago),
+        "updatedAt": now - Duration::hours(hours_ago / 3),
+        "repository": { "nameWithOwner": repo },
+        "author": { "login": author },
+        "labels": { "nodes": [] },
+    })
+}
+
+fn notification(
+    id: &str,
+    reason: &str,
+    title: &str,
+    repo: &str,
+    subject: &str,
+    hours_ago: i64,
+) -> Value {
+    let (subject_type, url) = match subject.split_once('/') {
+        Some(("pulls", _)) => (
+            "PullRequest",
+            Some(format!("https://api.github.com/repos/{repo}/{subject}")),
+        ),
+        Some(("issues", _)) => (
+            "Issue",
+            Some(format!("https://api.github.com/repos/{repo}/{subject}")),
+        ),
+        _ => (subject, None),
+    };
+    json!({
+        "id": id,
+        "unread": true,
+        "reason": reason,
+        "updated_at": Utc::now() - Duration::hours(hours_ago),
+        "subject": { "title": title, "url": url, "type": subject_type },
+        "repository": {
+            "full_name": repo,
+            "html_url": format!("https://github.com/{repo}"),
+        },
+    })
+}
+
+fn demo_notifications() -> Value {
+    json!([
+        notification(
+            "9001",
+            "mention",
+            "Flaky login test on CI",
+            "acme/web-app",
+            "issues/802",
+            1
+        ),
+        notification(
+            "9002",
+            "team_mention",
+            "Postgres 16 rollout plan",
+            "acme/infra",
+            "issues/1203",
+            5
+        ),
+        notification(
+            "9003",
+            "comment",
+            "Add OAuth2 PKCE flow",
+            "acme/backend",
+            "pulls/342",
+            2
+        ),
+        notification(
+            "9004",
+            "subscribed",
+            "Q3 architecture review",
+            "acme/backend",
+            "Discussion",
+            30
+        ),
+    ])
+}
+
+/// `total` review threads, the first `unresolved` of them still open.
+fn threads(total: u32, unresolved: u32) -> Value {
+    let nodes: Vec<Value> = (0..total)
+        .map(|i| json!({ "isResolved": i >= unresolved }))
+        .collect();
+    json!({ "totalCount": total, "nodes": nodes })
+}
+
+fn search(nodes: Vec<Value>) -> Value {
+    json!({
+        "issueCount": nodes.len(),
+        "pageInfo": { "hasNextPage": false, "endCursor": null },
+        "nodes": nodes,
+    })
+}
+
+#[rustfmt::skip]
+fn demo_team_prs() -> Value {
+    let pr = |id, number, title, repo, author, ci, hours_ago| DemoPr {
+        id, number, title, repo, author, ci, hours_ago,
+    };
+    search(vec![
+        pr("t1", 351, "Split billing service config", "acme/backend", "olivia-dev", Some("SUCCESS"), 20).with(json!({
+            "reviewDecision": "APPROVED",
+        })),
+        pr("t2", 352, "Retry S3 uploads with backoff", "acme/backend", "olivia-dev", Some("FAILURE"), 6).to_json(),
+        pr("t3", 1210, "Terraform: tag all EBS volumes", "acme/infra", "james-eng", Some("SUCCESS"), 30).with(json!({
+            "reviewDecision": "CHANGES_REQUESTED",
+        })),
+        pr("t4", 902, "Skeleton loaders for dashboard", "acme/web-app", "sarah-ui", Some("PENDING"), 3).to_json(),
+    ])
+}
+
+#[rustfmt::skip]
+fn demo_search_response() -> Value {
+    let pr = |id, number, title, repo, author, ci, hours_ago| DemoPr {
+        id, number, title, repo, author, ci, hours_ago,
+    };
+
+    let now = Utc::now();
+    let my_review = |minutes_ago| json!({ "nodes": [
+        { "author": { "login": DEMO_VIEWER }, "state": "COMMENTED", "submittedAt": now - Duration::minutes(minutes_ago) },
+    ] });
+
+    let needs_review = vec![
+        // Re-requested after your review
+        pr("d1", 342, "Add OAuth2 PKCE flow", "acme/backend", "olivia-dev", Some("SUCCESS"), 2).with(json!({
+            "latestReviews": my_review(90),
+            "timelineItems": { "nodes": [
+                { "createdAt": now - Duration::minutes(20), "requestedReviewer": { "login": DEMO_VIEWER } },
+            ] },
+        })),
+        // New commits since your review
+        pr("d2", 187, "Migrate users table to UUIDs", "acme/backend", "james-eng", Some("SUCCESS"), 5).with(json!({
+            "latestReviews": my_review(240),
+        })),
+        pr("d3", 891, "Add dark mode support", "acme/web-app", "sarah-ui", Some("PENDING"), 1).to_json(),
+        pr("d4", 56, "Bump dependencies (Feb 2026)", "acme/infra", "dependabot", Some("SUCCESS"), 8).to_json(),
+        // Requested from a team, not from you directly
+        pr("d15", 412, "Upgrade to Postgres 16", "acme/infra", "james-eng", Some("SUCCESS"), 10).with(json!({
+            "reviewRequests": { "nodes": [
+                { "requestedReviewer": { "name": "Platform", "combinedSlug": "acme/platform" } },
+            ] },
+        })),
+    ];
+
+    let authored = vec![
+        // Returned to You (changes requested)
+        pr("d5", 204, "Refactor payment processing", "acme/backend", DEMO_VIEWER, Some("FAILURE"), 24).with(json!({
+            "reviewDecision": "CHANGES_REQUESTED",
+            "reviewThreads": threads(5, 3),
+        })),
+        pr("d6", 723, "Fix race condition in queue worker", "acme/backend", DEMO_VIEWER, Some("SUCCESS"), 48).with(json!({
+            "reviewDecision": "CHANGES_REQUESTED",
+            "headRefName": "queue-worker-race",
+            "reviewThreads": threads(2, 2),
+        })),
+        // Approved
+        pr("d7", 445, "Add retry logic to webhook delivery", "acme/backend", DEMO_VIEWER, Some("SUCCESS"), 3).with(json!({
+            "reviewDecision": "APPROVED",
+            "labels": { "nodes": [{ "name": "P0", "color": "b60205" }] },
+            "mergeQueueEntry": { "position": 3, "state": "QUEUED" },
+        })),
+        pr("d8", 112, "Update onboarding flow copy", "acme/web-app", DEMO_VIEWER, Some("SUCCESS"), 6).with(json!({
+            "reviewDecision": "APPROVED",
+            "autoMergeRequest": { "enabledAt": now - Duration::hours(1) },
+        })),
+        // Waiting for Reviewers
+        // Stacked: #890 builds on #723, #905 builds on #890
+        pr("d9", 890, "Implement rate limiting middleware", "acme/backend", DEMO_VIEWER, Some("SUCCESS"), 12).with(json!({
+            "mergeStateStatus": "BEHIND",
+            "baseRefName": "queue-worker-race",
+            "headRefName": "rate-limiting",
+        })),
+        pr("d18", 905, "Rate limit per API key", "acme/backend", DEMO_VIEWER, Some("SUCCESS"), 8).with(json!({
+            "baseRefName": "rate-limiting",
+        })),
+        pr("d10", 334, "Add E2E tests for checkout", "acme/web-app", DEMO_VIEWER, Some("PENDING"), 4).with(json!({
+            "mergeable": "CONFLICTING",
+            "mergeStateStatus": "DIRTY",
+        })),
+        // Drafts
+        pr("d12", 901, "WIP: New dashboard layout", "acme/web-app", DEMO_VIEWER, None, 168).with(json!({
+            "isDraft": true,
+            "labels": { "nodes": [{ "name": "wip", "color": "fbca04" }] },
+        })),
+    ];
+
+    let reviewed = |state, minutes_ago| json!({ "nodes": [
+        { "author": { "login": DEMO_VIEWER }, "state": state, "submittedAt": now - Duration::minutes(minutes_ago) },
+    ] });
+    let reviewed_by_me = vec![
+        // Updated since you requested changes
+        pr("d11", 567, "Add GraphQL subscriptions", "acme/backend", "mike-gql", Some("SUCCESS"), 72).with(json!({
+            "latestReviews": reviewed("CHANGES_REQUESTED", 48 * 60),
+            "reviewDecision": "CHANGES_REQUESTED",
+        })),
+        pr("d16", 615, "Cache feature flags per request", "acme/web-app", "sarah-ui", Some("SUCCESS"), 30).with(json!({
+            "latestReviews": reviewed("APPROVED", 60),
+            "reviewDecision": "APPROVED",
+        })),
+        pr("d17", 98, "Document on-call runbook", "acme/infra", "olivia-dev", None, 20).with(json!({
+            "latestReviews": reviewed("COMMENTED", 5 * 60),
+        })),
+    ];
+
+    let recently_merged = vec![
+        pr("d13", 200, "Fix memory leak in connection pool", "acme/backend", DEMO_VIEWER, None, 26).merged(),
+        pr("d14", 88, "Add Terraform module for Redis", "acme/infra", DEMO_VIEWER, None, 50).merged(),
+    ];
+
+    json!({
+        "data": {
+            "needsReview": search(needs_review),
+            "authored": search(authored),
+            "reviewedByMe": search(reviewed_by_me),
+            "recentlyMerged": search(recently_merged),
+            "rateLimit": { "cost": 1, "remaining": 4999, "resetAt": Utc::now() + Duration::hours(1) },
+        }
+    })
+}
@@ -1,145 +1,276 @@
 use anyhow::{Context, Result, bail};
 use chrono::{Duration, Utc};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::process::Command;
-use std::sync::OnceLock;
 
-use crate::config::AppConfig;
+use crate::backend::GitHubBackend;
+use crate::config::{
+    AppConfig, BotPrHandling, DEFAULT_HOST, SavedSearch, StalePrHandling, TEAM_SECTION_ID,
+};
 use crate::models::*;
 
-// ── gh CLI path resolution ─────────────────────────────────────────────────
-
-static GH_PATH: OnceLock<String> = OnceLock::new();
-
-/// Resolve the full path to `gh` CLI. Searches common Homebrew/system paths
-/// since bundled macOS apps don't inherit the user's shell PATH.
-fn gh_bin() -> &'static str {
-    GH_PATH.get_or_init(|| {
-        let candidates = [
-            "/opt/homebrew/bin/gh",          // Apple Silicon Homebrew
-            "/usr/local/bin/gh",             // Intel Homebrew
-            "/usr/bin/gh",                   // System
-            "/run/current-system/sw/bin/gh", // NixOS
-        ];
-
-        // Try common known paths first
-        for path in &candidates {
-            if std::path::Path::new(path).exists() {
-                return path.to_string();
-            }
-        }
-
-        // Fallback: ask the user's login shell where gh lives
-        if let Ok(output) = Command::new("/bin/sh")
-            .args(["-l", "-c", "which gh"])
-            .output()
-        {
-            let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if !path.is_empty() && std::path::Path::new(&path).exists() {
-                return path;
-            }
-        }
+// ── GraphQL query builder ───────────────────────────────────────────────────
 
-        // Last resort: hope it's in PATH
-        "gh".to_string()
-    })
-}
+/// Page size for each aliased search (GitHub caps search pages at 100).
+const PAGE_SIZE: u32 = 50;
 
-// ── GraphQL query builder ───────────────────────────────────────────────────
+/// GitHub rejects search queries longer than this.
+const MAX_SEARCH_QUERY_LEN: usize = 256;
 
-fn build_query(merged_since: &str) -> String {
-    let pr_fields = r#"
+/// Fields every PR search fetches.
+const PR_FIELDS: &str = r#"
         id number title url isDraft createdAt updatedAt
-        repository { nameWithOwner }
+        repository { nameWithOwner defaultBranchRef { name } }
         author { login }
         reviewDecision
         latestReviews(first: 10) {
           nodes { author { login } state submittedAt }
         }
+        baseRefName headRefName isCrossRepository
+        additions deletions changedFiles
+        labels(first: 20) { nodes { name color } }
+        commits(last: 1) {
+          nodes { commit { oid committedDate statusCheckRollup { state } } }
+        }"#;
+
+/// Who the review is requested from, and when. Only the review-request
+/// search reads them.
+const REVIEW_REQUEST_FIELDS: &str = r#"
         reviewRequests(first: 10) {
-          nodes { requestedReviewer { ... on User { login } ... on Team { name } } }
+          nodes { requestedReviewer { ... on User { login } ... on Team { name combinedSlug } } }
         }
+        timelineItems(itemTypes: [REVIEW_REQUESTED_EVENT], last: 5) {
+          nodes { ... on ReviewRequestedEvent {
+            createdAt
+            requestedReviewer { ... on User { login } ... on Team { combinedSlug } }
+          } }
+        }"#;
+
+/// Extra fields for the viewer's own PRs, only shown on their rows. GitHub
+/// has no unresolved thread count, so threads come as bare `isResolved` flags.
+/// The `commits` selection merges with the shared one in `PR_FIELDS`.
+const AUTHORED_PR_FIELDS: &str = r#"
+        mergeable mergeStateStatus
+        autoMergeRequest { enabledAt }
+        reviewThreads(first: 100) { totalCount nodes { isResolved } }
         commits(last: 1) {
-          nodes { commit { oid committedDate statusCheckRollup { state } } }
+          nodes { commit { statusCheckRollup { contexts(first: 25) { nodes {
+            ... on CheckRun { name conclusion detailsUrl }
+            ... on StatusContext { context state targetUrl }
+          } } } } }
         }"#;
 
+/// Merge queue position for authored PRs. GHES releases without merge
+/// queues reject the field, so it's dropped there.
+const MERGE_QUEUE_FIELDS: &str = r#"
+        mergeQueueEntry { position state }"#;
+
+const ISSUE_FIELDS: &str = r#"
+      id number title url createdAt updatedAt
+      repository { nameWithOwner }
+      author { login }
+      labels(first: 20) { nodes { name color } }"#;
+
+const MERGED_PR_FIELDS: &str = r#"
+      id number title url createdAt mergedAt
+      repository { nameWithOwner }
+      author { login }"#;
+
+/// One aliased `search` in the combined query.
+struct SearchSpec {
+    alias: String,
+    query: String,
+    /// `PullRequest` or `Issue`
+    node_type: &'static str,
+    /// Field fragments, joined in order
+    fields: &'static [&'static str],
+}
+
+/// `merge_queue` adds the merge queue fields to the authored search.
+fn search_specs(
+    merged_since: &str,
+    saved: &[SavedSearch],
+    issues: bool,
+    team: &[String],
+    merge_queue: bool,
+) -> Vec<SearchSpec> {
+    let pr_search = |alias: &str, query: String, fields| SearchSpec {
+        alias: alias.to_string(),
+        query,
+        node_type: "PullRequest",
+        fields,
+    };
+    let issue_search = |alias: &str, query: &str| SearchSpec {
+        alias: alias.to_string(),
+        query: query.to_string(),
+        node_type: "Issue",
+        fields: &[ISSUE_FIELDS],
+    };
+
+    let mut specs = vec![
+        pr_search(
+            "needsReview",
+            "is:pr is:open review-requested:@me".to_string(),
+            &[PR_FIELDS, REVIEW_REQUEST_FIELDS],
+        ),
+        pr_search(
+            "authored",
+            "is:pr is:open author:@me".to_string(),
+            if merge_queue {
+                &[PR_FIELDS, AUTHORED_PR_FIELDS, MERGE_QUEUE_FIELDS]
+            } else {
+                &[PR_FIELDS, AUTHORED_PR_FIELDS]
+            },
+        ),
+        pr_search(
+            "reviewedByMe",
+            "is:pr is:open reviewed-by:@me -author:@me -review-requested:@me".to_string(),
+            &[PR_FIELDS],
+        ),
+        pr_search(
+            "recentlyMerged",
+            format!("is:pr is:merged author:@me merged:>{merged_since}"),
+            &[MERGED_PR_FIELDS],
+        ),
+    ];
+    if issues {
+        specs.push(issue_search(
+            "assignedIssues",
+            "is:issue is:open assignee:@me",
+        ));
+        specs.push(issue_search(
+            "mentionedIssues",
+            "is:issue is:open mentions:@me -assignee:@me",
+        ));
+    }
+    specs.extend(
+        saved.iter().enumerate().map(|(i, search)| {
+            pr_search(&saved_search_alias(i), search.search_query(), &[PR_FIELDS])
+        }),
+    );
+    specs.extend(
+        team_search_queries(team)
+            .into_iter()
+            .enumerate()
+            .map(|(i, query)| pr_search(&team_search_alias(i), query, &[PR_FIELDS])),
+    );
+    specs
+}
+
+/// Open PRs authored by any of `logins`, split into as many searches as it
+/// takes to stay under GitHub's query length limit. Repeated `author:`
+/// qualifiers match any of them.
+fn team_search_queries(logins: &[String]) -> Vec<String> {
+    const BASE: &str = "is:pr is:open -author:@me";
+    let mut queries = Vec::new();
+    let mut query = BASE.to_string();
+    for login in logins {
+        let qualifier = format!(" author:{login}");
+        if query.len() + qualifier.len() > MAX_SEARCH_QUERY_LEN && query != BASE {
+            queries.push(std::mem::replace(&mut query, BASE.to_string()));
+        }
+        query.push_str(&qualifier);
+    }
+    if query != BASE {
+        queries.push(query);
+    }
+    queries
+}
+
+/// One aliased `search` field, resuming after `cursor` if given.
+fn search_field(spec: &SearchSpec, cursor: Option<&str>, first: u32) -> String {
+    let after = cursor
+        .map(|c| format!(", after: \"{c}\""))
+        .unwrap_or_default();
+    // Saved searches may quote phrases: label:"needs review"
+    let search = spec.query.replace('\\', "\\\\").replace('"', "\\\"");
     format!(
-        r#"{{
-  needsReview: search(query: "is:pr is:open review-requested:@me", type: ISSUE, first: 50) {{
-    issueCount
-    nodes {{ ... on PullRequest {{ {pr_fields} }} }}
-  }}
-  authored: search(query: "is:pr is:open author:@me", type: ISSUE, first: 50) {{
-    issueCount
-    nodes {{ ... on PullRequest {{ {pr_fields} }} }}
-  }}
-  reviewedByMe: search(query: "is:pr is:open reviewed-by:@me -author:@me -review-requested:@me", type: ISSUE, first: 50) {{
+        r#"  {alias}: search(query: "{search}", type: ISSUE, first: {first}{after}) {{
     issueCount
-    nodes {{ ... on PullRequest {{ {pr_fields} }} }}
+    pageInfo {{ hasNextPage endCursor }}
+    nodes {{ ... on {node_type} {{ {fields} }} }}
   }}
-  recentlyMerged: search(query: "is:pr is:merged author:@me merged:>{merged_since}", type: ISSUE, first: 20) {{
-    issueCount
-    nodes {{ ... on PullRequest {{
-      id number title url createdAt mergedAt
-      repository {{ nameWithOwner }}
-      author {{ login }}
-    }} }}
-  }}
-}}"#
+"#,
+        alias = spec.alias,
+        node_type = spec.node_type,
+        fields = spec.fields.concat(),
     )
 }
 
-// ── gh CLI status check ─────────────────────────────────────────────────────
+/// Build a query with one aliased search per entry. Entries with a cursor
+/// resume from that page.
+fn build_query(searches: &[(&SearchSpec, Option<&str>, u32)]) -> String {
+    let mut query = String::from("{\n");
+    for (spec, cursor, first) in searches {
+        query.push_str(&search_field(spec, *cursor, *first));
+    }
+    query.push_str("  rateLimit { cost remaining resetAt }\n");
+    query.push('}');
+    query
+}
+
+// ── Backend status ──────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone)]
 pub enum GhStatus {
     Ok,
     NotInstalled,
     NotAuthenticated(String),
+    Unreachable(String),
 }
 
-/// Quick check: is `gh` installed and authenticated?
-pub fn check_gh_status() -> GhStatus {
-    let bin = gh_bin();
-    if bin == "gh"
-        && Command::new("which")
-            .arg("gh")
-            .output()
-            .map(|o| !o.status.success())
-            .unwrap_or(true)
-    {
-        // If gh_bin() fell back to "gh" and `which` can't find it
-        if Command::new(bin).arg("--version").output().is_err() {
-            return GhStatus::NotInstalled;
-        }
-    }
+// ── Fetching ────────────────────────────────────────────────────────────────
 
-    match Command::new(bin).args(["auth", "status"]).output() {
-        Err(_) => GhStatus::NotInstalled,
-        Ok(output) => {
-            if output.status.success() {
-                GhStatus::Ok
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-                GhStatus::NotAuthenticated(stderr)
-            }
-        }
+pub fn get_viewer_login(backend: &dyn GitHubBackend) -> Result<String> {
+    let stdout = backend.graphql("{ viewer { login } }")?;
+
+    #[derive(Deserialize)]
+    struct Resp {
+        data: Data,
+    }
+    #[derive(Deserialize)]
+    struct Data {
+        viewer: Viewer,
+    }
+    #[derive(Deserialize)]
+    struct Viewer {
+        login: String,
     }
-}
 
-// ── gh CLI interaction ──────────────────────────────────────────────────────
+    let resp: Resp = serde_json::from_slice(&stdout)?;
+    Ok(resp.data.viewer.login)
+}
 
-pub fn get_viewer_login() -> Result<String> {
-    let output = Command::new(gh_bin())
-        .args(["api", "graphql", "-f", "query={ viewer { login } }"])
-        .output()
-        .context("Failed to execute `gh` CLI. Is it installed?")?;
+/// Error for a response without any data: the first GraphQL error, typed.
+fn whole_query_error(errors: &[GqlError]) -> anyhow::Error {
+    match errors.first() {
+        Some(err) => ApiError::from(err).into(),
+        None => anyhow::anyhow!("GraphQL response contained no data"),
+    }
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("gh auth check failed: {}", stderr.trim());
+/// `value` checked to be a login or slug, so it can go inside a quoted
+/// GraphQL argument as-is.
+fn graphql_name<'a>(what: &str, value: &'a str) -> Result<&'a str> {
+    let valid = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+    if !valid {
+        bail!("\"{value}\" is not a valid {what}: use letters, digits, '-', '_' and '.' only");
     }
+    Ok(value)
+}
+
+/// Teams `login` belongs to, as "org/team-slug". Needs the `read:org` scope.
+pub fn get_viewer_teams(backend: &dyn GitHubBackend, login: &str) -> Result<Vec<String>> {
+    let login = graphql_name("login", login)?;
+    let query = format!(
+        r#"{{ viewer {{ organizations(first: 100) {{ nodes {{
+  teams(first: 100, userLogins: ["{login}"]) {{ nodes {{ combinedSlug }} }}
+}} }} }} }}"#
+    );
+    let stdout = backend.graphql(&query)?;
 
     #[derive(Deserialize)]
     struct Resp {
@@ -147,43 +278,413 @@ pub fn get_viewer_login() -> Result<String> {
     }
     #[derive(Deserialize)]
     struct Data {
-        viewer: Viewer,
+        viewer: ViewerOrgs,
     }
     #[derive(Deserialize)]
-    struct Viewer {
-        login: String,
+    struct ViewerOrgs {
+        organizations: Nodes<Org>,
+    }
+    #[derive(Deserialize)]
+    struct Org {
+        teams: Nodes<Team>,
+    }
+    #[derive(Deserialize)]
+    struct Team {
+        #[serde(rename = "combinedSlug")]
+        combined_slug: String,
+    }
+    #[derive(Deserialize)]
+    struct Nodes<T> {
+        nodes: Vec<T>,
     }
 
-    let resp: Resp = serde_json::from_slice(&output.stdout)?;
-    Ok(resp.data.viewer.login)
+    let resp: Resp = serde_json::from_slice(&stdout).context("Failed to parse team memberships")?;
+    Ok(resp
+        .data
+        .viewer
+        .organizations
+        .nodes
+        .into_iter()
+        .flat_map(|org| org.teams.nodes)
+        .map(|team| team.combined_slug)
+        .collect())
 }
 
-pub fn fetch_prs(merged_days: i64) -> Result<GqlResponse> {
+/// Login and team memberships. Teams are best-effort: without `read:org`
+/// team requests can't be matched to a membership and count as direct ones.
+pub fn get_viewer(backend: &dyn GitHubBackend) -> Result<Viewer> {
+    let login = get_viewer_login(backend)?;
+    let teams = get_viewer_teams(backend, &login).unwrap_or_else(|e| {
+        crate::logging::log_error(&format!("Failed to fetch team memberships: {e:#}"));
+        Vec::new()
+    });
+    Ok(Viewer { login, teams })
+}
+
+/// Logins of the members of `slug` ("org/team-slug"). Needs `read:org`.
+pub fn get_team_members(backend: &dyn GitHubBackend, slug: &str) -> Result<Vec<String>> {
+    let Some((org, team)) = slug.split_once('/') else {
+        bail!("Team should be given as \"org/team-slug\", not \"{slug}\"");
+    };
+    let org = graphql_name("organization", org.trim())?;
+    let team = graphql_name("team slug", team.trim())?;
+    let query = format!(
+        r#"{{ organization(login: "{org}") {{ team(slug: "{team}") {{
+  members(first: 100) {{ nodes {{ login }} }}
+}} }} }}"#
+    );
+    let stdout = backend.graphql(&query)?;
+
+    #[derive(Deserialize)]
+    struct Data {
+        organization: Option<Org>,
+    }
+    #[derive(Deserialize)]
+    struct Org {
+        team: Option<Team>,
+    }
+    #[derive(Deserialize)]
+    struct Team {
+        members: Members,
+    }
+    #[derive(Deserialize)]
+    struct Members {
+        nodes: Vec<Actor>,
+    }
+
+    let raw: RawResponse<Data> =
+        serde_json::from_slice(&stdout).context("Failed to parse team members")?;
+    let Some(data) = raw.data else {
+        return Err(whole_query_error(&raw.errors));
+    };
+    let Some(team) = data.organization.and_then(|org| org.team) else {
+        bail!("Team {slug} not found, or not visible to this account");
+    };
+    Ok(team.members.nodes.into_iter().map(|m| m.login).collect())
+}
+
+/// Everyone the team view covers: the team's members plus the extra
+/// logins, sorted and deduplicated.
+pub fn team_logins(backend: &dyn GitHubBackend, config: &AppConfig) -> Result<Vec<String>> {
+    let mut logins = config.team_members.clone();
+    if let Some(slug) = &config.team_slug {
+        logins.extend(get_team_members(backend, slug)?);
+    }
+    logins.sort_by_key(|login| login.to_lowercase());
+    logins.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    Ok(logins)
+}
+
+/// `data` is null when the whole query failed; `errors` explains why.
+#[derive(Deserialize)]
+struct RawResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GqlError>,
+}
+
+/// Fetch all searches, following `pageInfo` cursors until each search is
+/// exhausted or holds `max_per_search` PRs. `team` lists the logins whose
+/// open PRs the team view shows.
+pub fn fetch_prs(
+    backend: &dyn GitHubBackend,
+    merged_days: i64,
+    max_per_search: u32,
+    saved: &[SavedSearch],
+    issues: bool,
+    team: &[String],
+) -> Result<GqlResponse> {
     let merged_since = (Utc::now() - Duration::days(merged_days))
         .format("%Y-%m-%d")
         .to_string();
 
-    let query = build_query(&merged_since);
+    let max = max_per_search.max(1);
+    let first_page = |specs: &[SearchSpec]| -> Result<RawResponse<GqlData>> {
+        let searches: Vec<_> = specs
+            .iter()
+            .map(|spec| (spec, None, PAGE_SIZE.min(max)))
+            .collect();
+        let stdout = backend.graphql(&build_query(&searches))?;
+        serde_json::from_slice(&stdout).context("Failed to parse GraphQL response")
+    };
+
+    let mut specs = search_specs(&merged_since, saved, issues, team, true);
+    let mut raw = first_page(&specs)?;
+    // GHES without merge queues fails the whole query on the field
+    if raw.data.is_none()
+        && raw
+            .errors
+            .iter()
+            .any(|e| e.message.contains("mergeQueueEntry"))
+    {
+        specs = search_specs(&merged_since, saved, issues, team, false);
+        raw = first_page(&specs)?;
+    }
+    let Some(data) = raw.data else {
+        return Err(whole_query_error(&raw.errors));
+    };
+    let mut response = GqlResponse {
+        data,
+        errors: raw.errors,
+    };
 
-    let output = Command::new(gh_bin())
-        .args(["api", "graphql", "-f", &format!("query={query}")])
-        .output()
-        .context("Failed to execute `gh` CLI. Is it installed?")?;
+    #[derive(Deserialize)]
+    struct PageData {
+        #[serde(rename = "rateLimit", default)]
+        rate_limit: Option<RateLimit>,
+        /// PR and issue searches, parsed once the alias says which
+        #[serde(flatten)]
+        searches: HashMap<String, serde_json::Value>,
+    }
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if stderr.contains("not logged in") || stderr.contains("authentication") {
-            bail!("gh CLI is not authenticated. Run `gh auth login` first.");
+    loop {
+        let mut pending: Vec<(&SearchSpec, String, u32)> = Vec::new();
+        for spec in &specs {
+            let next = if let Some(result) = response.data.search_mut(&spec.alias) {
+                next_page(result, max)
+            } else if let Some(result) = response.data.issue_search_mut(&spec.alias) {
+                next_page(result, max)
+            } else {
+                None
+            };
+            if let Some((cursor, first)) = next {
+                pending.push((spec, cursor, first));
+            }
+        }
+        if pending.is_empty() {
+            break;
         }
-        bail!("gh api graphql failed: {}", stderr.trim());
-    }
 
-    let response: GqlResponse =
-        serde_json::from_slice(&output.stdout).context("Failed to parse GraphQL response")?;
+        let searches: Vec<_> = pending
+            .iter()
+            .map(|(spec, cursor, first)| (*spec, Some(cursor.as_str()), *first))
+            .collect();
+        let stdout = backend.graphql(&build_query(&searches))?;
+        let page: RawResponse<PageData> =
+            serde_json::from_slice(&stdout).context("Failed to parse GraphQL response")?;
+        response.errors.extend(page.errors);
+        // Keep what the earlier pages returned if a later one fails outright
+        let Some(page) = page.data else {
+            break;
+        };
+
+        for (alias, next) in page.searches {
+            if let Some(result) = response.data.search_mut(&alias) {
+                merge_page(result, next)?;
+            } else if let Some(result) = response.data.issue_search_mut(&alias) {
+                merge_page(result, next)?;
+            }
+        }
+
+        // Keep the latest budget but count every page towards the cost
+        if let Some(mut latest) = page.rate_limit {
+            latest.cost += response.data.rate_limit.map_or(0, |r| r.cost);
+            response.data.rate_limit = Some(latest);
+        }
+    }
 
     Ok(response)
 }
 
+/// Cursor and page size for the next page of `result`, if it has more and
+/// is still under `max`.
+fn next_page<T>(result: &SearchResult<T>, max: u32) -> Option<(String, u32)> {
+    let fetched = result.nodes.len() as u32;
+    let page = result.page_info.as_ref()?;
+    if !page.has_next_page || fetched >= max {
+        return None;
+    }
+    let cursor = page.end_cursor.clone()?;
+    Some((cursor, PAGE_SIZE.min(max - fetched)))
+}
+
+/// Append a later page to `result`.
+fn merge_page<T: serde::de::DeserializeOwned>(
+    result: &mut SearchResult<T>,
+    next: serde_json::Value,
+) -> Result<()> {
+    let next: Option<SearchResult<T>> =
+        serde_json::from_value(next).context("Failed to parse GraphQL response")?;
+    match next {
+        Some(next) => {
+            result.nodes.extend(next.nodes);
+            result.page_info = next.page_info;
+            result.issue_count = next.issue_count;
+        }
+        // This search failed; stop paging it
+        None => result.page_info = None,
+    }
+    Ok(())
+}
+
+/// Result of checking a saved search before it's added.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchCheck {
+    /// PRs the search matches right now
+    pub issue_count: u32,
+    /// Rate-limit points its first page adds to every poll
+    pub cost: u32,
+}
+
+/// Run `search` once so GitHub reports syntax errors, then price its first
+/// page with a `rateLimit(dryRun: true)` query.
+pub fn check_saved_search(
+    backend: &dyn GitHubBackend,
+    search: &SavedSearch,
+    max_per_search: u32,
+) -> Result<SearchCheck> {
+    if search.searches_issues() {
+        bail!("Saved searches can only show pull requests. Remove is:issue / type:issue.");
+    }
+    let probe = SearchSpec {
+        alias: "check".to_string(),
+        query: search.search_query(),
+        node_type: "PullRequest",
+        fields: &["id"],
+    };
+    let stdout = backend.graphql(&format!("{{\n{}}}", search_field(&probe, None, 1)))?;
+
+    #[derive(Deserialize)]
+    struct Probe {
+        check: Option<Count>,
+    }
+    #[derive(Deserialize)]
+    struct Count {
+        #[serde(rename = "issueCount")]
+        issue_count: u32,
+    }
+
+    let raw: RawResponse<Probe> =
+        serde_json::from_slice(&stdout).context("Failed to parse search check")?;
+    let count = raw.data.and_then(|data| data.check);
+    let Some(count) = count.filter(|_| raw.errors.is_empty()) else {
+        return Err(whole_query_error(&raw.errors));
+    };
+
+    let spec = SearchSpec {
+        fields: &[PR_FIELDS],
+        ..probe
+    };
+    let query = format!(
+        "{{\n  rateLimit(dryRun: true) {{ cost }}\n{}}}",
+        search_field(&spec, None, PAGE_SIZE.min(max_per_search.max(1)))
+    );
+    let stdout = backend.graphql(&query)?;
+
+    #[derive(Deserialize)]
+    struct DryRun {
+        #[serde(rename = "rateLimit")]
+        rate_limit: Cost,
+    }
+    #[derive(Deserialize)]
+    struct Cost {
+        cost: u32,
+    }
+
+    let raw: RawResponse<DryRun> =
+        serde_json::from_slice(&stdout).context("Failed to parse rate-limit dry run")?;
+    let Some(dry_run) = raw.data else {
+        return Err(whole_query_error(&raw.errors));
+    };
+    Ok(SearchCheck {
+        issue_count: count.issue_count,
+        cost: dry_run.rate_limit.cost,
+    })
+}
+
+// ── Notifications inbox ─────────────────────────────────────────────────────
+
+/// Poll interval to use when GitHub doesn't send `X-Poll-Interval`.
+const DEFAULT_NOTIFICATION_POLL_SECS: i64 = 60;
+
+/// One account's unread notifications, kept between polls so an unchanged
+/// inbox costs a 304 instead of a full listing.
+#[derive(Debug, Clone, Default)]
+pub struct NotificationInbox {
+    pub notifications: Vec<Notification>,
+    /// `Last-Modified` of the last full listing, sent back as `If-Modified-Since`
+    last_modified: Option<String>,
+    /// GitHub's `X-Poll-Interval` asks clients not to poll before this
+    next_poll_at: Option<chrono::DateTime<Utc>>,
+}
+
+impl NotificationInbox {
+    /// Drop a thread after it was marked as read.
+    pub fn remove(&mut self, thread_id: &str) {
+        self.notifications.retain(|n| n.id != thread_id);
+    }
+}
+
+/// Refresh `inbox` from `GET /notifications`, unless GitHub's poll interval
+/// hasn't passed yet. A 304 keeps the cached threads.
+pub fn fetch_notifications(
+    backend: &dyn GitHubBackend,
+    inbox: &mut NotificationInbox,
+) -> Result<()> {
+    let now = Utc::now();
+    if inbox.next_poll_at.is_some_and(|at| now < at) {
+        return Ok(());
+    }
+
+    let mut headers = Vec::new();
+    if let Some(last_modified) = &inbox.last_modified {
+        headers.push(("If-Modified-Since", last_modified.as_str()));
+    }
+    let response = backend.rest("GET", "notifications?per_page=50", &headers)?;
+
+    let interval = response
+        .header("X-Poll-Interval")
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(DEFAULT_NOTIFICATION_POLL_SECS);
+    inbox.next_poll_at = Some(now + Duration::seconds(interval));
+
+    if response.status == 304 {
+        return Ok(());
+    }
+    inbox.notifications =
+        serde_json::from_slice(&response.body).context("Failed to parse notifications")?;
+    inbox.last_modified = response.header("Last-Modified").map(str::to_string);
+    Ok(())
+}
+
+/// Mark one notification thread as read.
+pub fn mark_notification_read(backend: &dyn GitHubBackend, thread_id: &str) -> Result<()> {
+    backend.rest("PATCH", &format!("notifications/threads/{thread_id}"), &[])?;
+    Ok(())
+}
+
+/// Mark every notification as read.
+pub fn mark_all_notifications_read(backend: &dyn GitHubBackend) -> Result<()> {
+    backend.rest("PUT", "notifications", &[])?;
+    Ok(())
+}
+
+/// Turn notification threads into `Notifications` rows. The row ID is the
+/// thread ID, which is what marking as read needs.
+pub fn categorize_notifications(
+    notifications: &[Notification],
+    host: &str,
+    account: &str,
+) -> Vec<CategorizedPr> {
+    notifications
+        .iter()
+        .map(|n| CategorizedPr {
+            id: n.id.clone(),
+            host: host.to_string(),
+            account: account.to_string(),
+            number: n.number(),
+            title: n.subject.title.clone(),
+            url: n.html_url(host),
+            repo: n.repository.full_name.clone(),
+            bucket: Bucket::Notifications,
+            created_at: n.updated_at,
+            updated_at: n.updated_at,
+            reason: Some(n.reason.clone()),
+            ..CategorizedPr::default()
+        })
+        .collect()
+}
+
 // ── Categorization engine ───────────────────────────────────────────────────
 
 fn extract_commit_info(
@@ -208,10 +709,11 @@ fn extract_commit_info(
         })
 }
 
-fn make_pr(pr: &PullRequest, bucket: Bucket) -> CategorizedPr {
+fn make_pr(pr: &PullRequest, host: &str, bucket: Bucket) -> CategorizedPr {
     let (sha, date, ci) = extract_commit_info(pr);
     CategorizedPr {
         id: pr.id.clone(),
+        host: host.to_string(),
         number: pr.number,
         title: pr.title.clone(),
         url: pr.url.clone(),
@@ -227,92 +729,458 @@ fn make_pr(pr: &PullRequest, bucket: Bucket) -> CategorizedPr {
         last_commit_sha: sha,
         last_commit_date: date,
         ci_status: ci,
+        additions: pr.additions,
+        deletions: pr.deletions,
+        changed_files: pr.changed_files,
+        labels: pr
+            .labels
+            .as_ref()
+            .map(|labels| labels.nodes.clone())
+            .unwrap_or_default(),
+        base_ref: pr.base_ref_name.clone(),
+        head_ref: stackable_head(pr),
+        ..CategorizedPr::default()
+    }
+}
+
+/// The PR's head branch, if other PRs can be stacked on it. Fork branches
+/// can't be another PR's base, and a release PR from the default branch
+/// (`main → production`) isn't the parent of every PR targeting `main`.
+fn stackable_head(pr: &PullRequest) -> Option<String> {
+    let head = pr.head_ref_name.as_ref()?;
+    let default_branch = pr.repository.default_branch_ref.as_ref();
+    if pr.is_cross_repository != Some(false) || default_branch.is_some_and(|b| &b.name == head) {
+        return None;
     }
+    Some(head.clone())
 }
 
-fn categorize_authored(pr: &PullRequest) -> CategorizedPr {
+fn make_issue(issue: &Issue, host: &str, bucket: Bucket) -> CategorizedPr {
+    CategorizedPr {
+        id: issue.id.clone(),
+        host: host.to_string(),
+        number: issue.number,
+        title: issue.title.clone(),
+        url: issue.url.clone(),
+        repo: issue.repository.name_with_owner.clone(),
+        author: issue
+            .author
+            .as_ref()
+            .map(|a| a.login.clone())
+            .unwrap_or_default(),
+        bucket,
+        created_at: issue.created_at,
+        updated_at: issue.updated_at,
+        labels: issue
+            .labels
+            .as_ref()
+            .map(|labels| labels.nodes.clone())
+            .unwrap_or_default(),
+        ..CategorizedPr::default()
+    }
+}
+
+/// Failed checks on the PR's last commit.
+fn failing_checks(pr: &PullRequest) -> Vec<FailingCheck> {
+    pr.commits
+        .as_ref()
+        .and_then(|c| c.nodes.first())
+        .and_then(|node| node.commit.status_check_rollup.as_ref())
+        .map(StatusCheck::failing_checks)
+        .unwrap_or_default()
+}
+
+/// `reviewDecision`, unless it rests only on bot reviews: a bot's approval
+/// or changes request doesn't mean a person looked at the PR.
+fn human_review_decision<'a>(pr: &'a PullRequest, config: &AppConfig) -> Option<&'a str> {
+    let decision = pr.review_decision.as_deref()?;
+    let (bots, humans): (Vec<&Review>, Vec<&Review>) = pr
+        .latest_reviews
+        .iter()
+        .flat_map(|reviews| &reviews.nodes)
+        .partition(|r| r.author.as_ref().is_some_and(|a| config.is_bot(&a.login)));
+    if bots.iter().all(|r| r.state != decision) {
+        return Some(decision);
+    }
+    humans
+        .iter()
+        .any(|r| r.state == decision)
+        .then_some(decision)
+}
+
+fn categorize_authored(pr: &PullRequest, host: &str, config: &AppConfig) -> CategorizedPr {
+    let (_, _, ci) = extract_commit_info(pr);
+    let ci_failing = matches!(ci.as_deref(), Some("FAILURE" | "ERROR"));
+    let decision = human_review_decision(pr, config);
     let bucket = if pr.is_draft.unwrap_or(false) {
         Bucket::Drafts
-    } else if pr.review_decision.as_deref() == Some("APPROVED") {
-        Bucket::Approved
-    } else if pr.review_decision.as_deref() == Some("CHANGES_REQUESTED") {
+    } else if decision == Some("CHANGES_REQUESTED") {
+        // Requested changes are the author's to-do whatever CI says
         Bucket::ReturnedToYou
+    } else if ci_failing {
+        Bucket::CiFailing
+    } else if decision == Some("APPROVED") {
+        Bucket::Approved
     } else {
         Bucket::WaitingForReviewers
     };
-    make_pr(pr, bucket)
+    let mut result = make_pr(pr, host, bucket);
+    if bucket == Bucket::CiFailing {
+        result.failing_checks = failing_checks(pr);
+    }
+    result.mergeable = pr.mergeable.clone();
+    result.merge_state_status = pr.merge_state_status.clone();
+    result.merge_queue = pr.merge_queue_entry.clone();
+    result.auto_merge = pr.auto_merge_request.is_some();
+    if let Some(threads) = &pr.review_threads {
+        result.unresolved_threads = Some(threads.unresolved());
+        result.total_threads = Some(threads.total_count);
+    }
+    result
 }
 
-fn categorize_reviewed_by_me(pr: &PullRequest, viewer: &str) -> CategorizedPr {
-    let _my_review = pr.latest_reviews.as_ref().and_then(|reviews| {
+/// Split PRs the viewer already reviewed by what that review said:
+/// changes requested (and whether the author pushed since), approved, or
+/// only commented.
+fn categorize_reviewed_by_me(pr: &PullRequest, host: &str, viewer: &str) -> CategorizedPr {
+    let my_review = pr.latest_reviews.as_ref().and_then(|reviews| {
         reviews
             .nodes
             .iter()
             .find(|r| r.author.as_ref().is_some_and(|a| a.login == viewer))
     });
 
-    make_pr(pr, Bucket::WaitingForAuthor)
+    let bucket = match my_review.map(|r| r.state.as_str()) {
+        Some("CHANGES_REQUESTED") => {
+            let (_, last_commit_at, _) = extract_commit_info(pr);
+            let reviewed_at = my_review.and_then(|r| r.submitted_at);
+            match (last_commit_at, reviewed_at) {
+                (Some(pushed), Some(reviewed)) if pushed > reviewed => {
+                    Bucket::UpdatedSinceYourReview
+                }
+                _ => Bucket::WaitingForAuthor,
+            }
+        }
+        Some("APPROVED") => Bucket::ApprovedByYou,
+        // Someone else's changes request still blocks the PR
+        _ if pr.review_decision.as_deref() == Some("CHANGES_REQUESTED") => Bucket::WaitingForAuthor,
+        _ => Bucket::CommentedByYou,
+    };
+
+    make_pr(pr, host, bucket)
+}
+
+/// A review request counts as direct when it names the viewer. Otherwise
+/// the search matched through a team; prefer a team the viewer is known to
+/// be on, since other teams may be requested too.
+fn categorize_review_request(pr: &PullRequest, host: &str, viewer: &Viewer) -> CategorizedPr {
+    let reviewers: Vec<&RequestedReviewer> = pr
+        .review_requests
+        .iter()
+        .flat_map(|requests| &requests.nodes)
+        .filter_map(|request| request.requested_reviewer.as_ref())
+        .collect();
+
+    let direct = reviewers
+        .iter()
+        .any(|r| r.login.as_deref() == Some(viewer.login.as_str()));
+    // Only teams the viewer is known to be on; without memberships (no
+    // `read:org`) the request stays the viewer's own
+    let my_team = reviewers.iter().find(|r| {
+        r.name.is_some()
+            && r.combined_slug
+                .as_ref()
+                .is_some_and(|slug| viewer.teams.contains(slug))
+    });
+    let mut result = match my_team {
+        Some(team) if !direct => {
+            let mut result = make_pr(pr, host, Bucket::TeamReviewRequested);
+            result.team = team.name.clone();
+            result
+        }
+        _ => make_pr(pr, host, Bucket::NeedsYourReview),
+    };
+    result.review_state = Some(review_sub_state(pr, viewer));
+    result
+}
+
+/// Compare the viewer's latest review with later review-request events
+/// (for the viewer or one of their teams) and the last commit. An explicit
+/// re-request wins over new commits.
+fn review_sub_state(pr: &PullRequest, viewer: &Viewer) -> ReviewSubState {
+    let my_review_at = pr
+        .latest_reviews
+        .iter()
+        .flat_map(|reviews| &reviews.nodes)
+        .filter(|r| r.author.as_ref().is_some_and(|a| a.login == viewer.login))
+        .filter_map(|r| r.submitted_at)
+        .max();
+    let Some(reviewed_at) = my_review_at else {
+        return ReviewSubState::NeverReviewed;
+    };
+
+    let for_viewer = |r: &RequestedReviewer| {
+        r.login.as_deref() == Some(viewer.login.as_str())
+            || r.combined_slug
+                .as_ref()
+                .is_some_and(|slug| viewer.teams.contains(slug))
+    };
+    let re_requested = pr
+        .timeline_items
+        .iter()
+        .flat_map(|items| &items.nodes)
+        .filter(|event| event.requested_reviewer.as_ref().is_some_and(for_viewer))
+        .any(|event| event.created_at.is_some_and(|at| at > reviewed_at));
+    if re_requested {
+        return ReviewSubState::ReRequested;
+    }
+
+    let (_, last_commit_at, _) = extract_commit_info(pr);
+    let still_requested = pr
+        .review_requests
+        .iter()
+        .flat_map(|requests| &requests.nodes)
+        .filter_map(|request| request.requested_reviewer.as_ref())
+        .any(for_viewer);
+    if last_commit_at.is_some_and(|at| at > reviewed_at) {
+        ReviewSubState::NewCommits
+    } else if still_requested {
+        // Requested after reviewing: the event just fell outside `last: 5`
+        ReviewSubState::ReRequested
+    } else {
+        // Search lag can list a PR whose request was already answered
+        ReviewSubState::NeverReviewed
+    }
 }
 
-pub fn categorize_all(data: &GqlData, viewer: &str) -> Vec<CategorizedPr> {
+/// Re-query the merge state of `prs` whose mergeability GitHub hadn't
+/// computed yet, and update them in place.
+pub fn refresh_merge_states(backend: &dyn GitHubBackend, prs: &mut [CategorizedPr]) -> Result<()> {
+    let ids: Vec<&str> = prs
+        .iter()
+        .filter(|pr| pr.merge_state_unknown())
+        .map(|pr| pr.id.as_str())
+        .collect();
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    // A JSON string list is also a valid GraphQL list literal
+    let query = format!(
+        "{{ nodes(ids: {}) {{ ... on PullRequest {{ id mergeable mergeStateStatus }} }} }}",
+        serde_json::to_string(&ids)?
+    );
+    let stdout = backend.graphql(&query)?;
+
+    #[derive(Deserialize)]
+    struct Data {
+        #[serde(deserialize_with = "skip_unmatched")]
+        nodes: Vec<MergeState>,
+    }
+
+    // Nodes that didn't resolve come back null next to an error; keep the rest
+    let raw: RawResponse<Data> =
+        serde_json::from_slice(&stdout).context("Failed to parse merge states")?;
+    let Some(data) = raw.data else {
+        return Err(whole_query_error(&raw.errors));
+    };
+    let states: HashMap<String, MergeState> = data
+        .nodes
+        .into_iter()
+        .map(|state| (state.id.clone(), state))
+        .collect();
+    for pr in prs {
+        if let Some(state) = states.get(&pr.id) {
+            pr.mergeable = state.mergeable.clone();
+            pr.merge_state_status = state.merge_state_status.clone();
+        }
+    }
+    Ok(())
+}
+
+/// Categorize one account's search results from `host`, deduplicating by
+/// node ID. `account` is the tag shown on each PR (empty for the default
+/// account); `config` says which reviewers are bots.
+pub fn categorize_all(
+    data: &GqlData,
+    host: &str,
+    account: &str,
+    viewer: &Viewer,
+    config: &AppConfig,
+) -> Vec<CategorizedPr> {
     let mut results = Vec::new();
     let mut seen_ids = HashSet::new();
 
     for pr in &data.needs_review.nodes {
         if seen_ids.insert(pr.id.clone()) {
-            results.push(make_pr(pr, Bucket::NeedsYourReview));
+            results.push(categorize_review_request(pr, host, viewer));
         }
     }
 
     for pr in &data.authored.nodes {
         if seen_ids.insert(pr.id.clone()) {
-            results.push(categorize_authored(pr));
+            results.push(categorize_authored(pr, host, config));
         }
     }
 
     for pr in &data.reviewed_by_me.nodes {
         if seen_ids.insert(pr.id.clone()) {
-            results.push(categorize_reviewed_by_me(pr, viewer));
+            results.push(categorize_reviewed_by_me(pr, host, &viewer.login));
         }
     }
 
     for pr in &data.recently_merged.nodes {
         if seen_ids.insert(pr.id.clone()) {
             results.push(CategorizedPr {
-                id: pr.id.clone(),
-                number: pr.number,
-                title: pr.title.clone(),
-                url: pr.url.clone(),
-                repo: pr.repository.name_with_owner.clone(),
-                author: pr
-                    .author
-                    .as_ref()
-                    .map(|a| a.login.clone())
-                    .unwrap_or_default(),
-                bucket: Bucket::RecentlyMerged,
-                created_at: pr.created_at,
                 updated_at: pr.merged_at, // use merged_at as the "updated" time
-                last_commit_sha: None,
                 last_commit_date: pr.merged_at,
-                ci_status: None,
+                ..make_pr(pr, host, Bucket::RecentlyMerged)
             });
         }
     }
 
+    for (bucket, issues) in [
+        (Bucket::AssignedIssues, &data.assigned_issues),
+        (Bucket::MentionedIssues, &data.mentioned_issues),
+    ] {
+        for issue in &issues.nodes {
+            if seen_ids.insert(issue.id.clone()) {
+                results.push(make_issue(issue, host, bucket));
+            }
+        }
+    }
+
+    // Saved searches list PRs whatever their bucket, so they dedupe on their own
+    for (i, search) in config.saved_searches.iter().enumerate() {
+        let Some(Some(result)) = data.saved.get(&saved_search_alias(i)) else {
+            continue;
+        };
+        let mut seen_ids = HashSet::new();
+        for pr in &result.nodes {
+            if seen_ids.insert(pr.id.clone()) {
+                let mut result = make_pr(pr, host, Bucket::SavedSearch);
+                result.search = Some(search.id());
+                results.push(result);
+            }
+        }
+    }
+
+    // Team PRs are categorized from their author's side and listed apart
+    let mut seen_ids = HashSet::new();
+    for result in data.team_searches() {
+        for pr in &result.nodes {
+            if seen_ids.insert(pr.id.clone()) {
+                let mut result = categorize_authored(pr, host, config);
+                result.search = Some(TEAM_SECTION_ID.to_string());
+                results.push(result);
+            }
+        }
+    }
+
+    for pr in &mut results {
+        pr.account = account.to_string();
+    }
+    link_stacks(&mut results);
     results
 }
 
+// ── Stacked PRs ─────────────────────────────────────────────────────────────
+
+/// Mark each PR whose base branch is the head branch of another open PR in
+/// the same repo as stacked on that PR. Merged PRs carry no branches, so a
+/// PR stops being stacked once its parent merges.
+fn link_stacks(prs: &mut [CategorizedPr]) {
+    let heads: HashMap<(String, String), u32> = prs
+        .iter()
+        .filter_map(|pr| Some(((pr.repo.clone(), pr.head_ref.clone()?), pr.number)))
+        .collect();
+    for pr in prs {
+        pr.stacked_on = pr
+            .base_ref
+            .as_ref()
+            .and_then(|base| heads.get(&(pr.repo.clone(), base.clone())))
+            .copied()
+            .filter(|parent| *parent != pr.number);
+    }
+}
+
+/// Order a section's rows so every stack reads top-down: each PR is followed
+/// by the PRs stacked on it, paired with their depth in the stack. PRs whose
+/// parent is in another section stay where they were, at depth 0.
+pub fn stack_order<'a>(prs: &[&'a CategorizedPr]) -> Vec<(&'a CategorizedPr, usize)> {
+    let is_parent = |parent: &CategorizedPr, child: &CategorizedPr| {
+        child.stacked_on == Some(parent.number)
+            && child.repo == parent.repo
+            && child.host == parent.host
+            && child.account == parent.account
+    };
+
+    let mut ordered = Vec::with_capacity(prs.len());
+    let mut placed = HashSet::new();
+    let roots = prs
+        .iter()
+        .filter(|pr| !prs.iter().any(|parent| is_parent(parent, pr)));
+    for root in roots {
+        let mut pending = vec![(*root, 0)];
+        while let Some((pr, depth)) = pending.pop() {
+            if !placed.insert(pr.key()) {
+                continue;
+            }
+            ordered.push((pr, depth));
+            // Reversed so the first child comes off the stack first
+            let children = prs.iter().rev().filter(|child| is_parent(pr, child));
+            pending.extend(children.map(|child| (*child, depth + 1)));
+        }
+    }
+    // A cycle of stacks has no root; list it flat
+    for pr in prs {
+        if placed.insert(pr.key()) {
+            ordered.push((pr, 0));
+        }
+    }
+    ordered
+}
+
 // ── Filtering ───────────────────────────────────────────────────────────────
 
 pub fn filter_prs(prs: Vec<CategorizedPr>, config: &AppConfig) -> Vec<CategorizedPr> {
-    if config.blocked_repos.is_empty() {
-        return prs;
-    }
+    let now = Utc::now();
     prs.into_iter()
         .filter(|pr| config.is_repo_allowed(&pr.repo))
+        .filter(|pr| !config.label_hidden(&pr.labels))
+        .filter(|pr| {
+            config.bot_handling(pr.section_id()) != BotPrHandling::Hide
+                || !config.is_bot(&pr.author)
+        })
+        .filter_map(|mut pr| {
+            if is_stale(&pr, config, now) {
+                match config.stale_prs {
+                    StalePrHandling::Hide => return None,
+                    StalePrHandling::Collapse => pr.bucket = Bucket::Stale,
+                }
+            }
+            Some(pr)
+        })
         .collect()
 }
 
+/// Open PRs whose last update is older than the repo's staleness threshold.
+fn is_stale(pr: &CategorizedPr, config: &AppConfig, now: chrono::DateTime<Utc>) -> bool {
+    // Saved searches say for themselves how old is too old
+    if matches!(
+        pr.bucket,
+        Bucket::RecentlyMerged | Bucket::SavedSearch | Bucket::Notifications
+    ) {
+        return false;
+    }
+    let Some(days) = config.stale_after_days_for(&pr.repo) else {
+        return false;
+    };
+    pr.updated_at
+        .or(pr.created_at)
+        .is_some_and(|at| now - at > Duration::days(days.into()))
+}
+
 /// Extract repos grouped by owner, sorted. Returns (owner, [(repo_full_name, pr_count)])
 pub fn extract_repo_tree(prs: &[CategorizedPr]) -> Vec<(String, Vec<(String, usize)>)> {
     let mut owner_repos: HashMap<String, HashMap<String, usize>> = HashMap::new();
@@ -341,49 +1209,23 @@ pub fn extract_repo_tree(prs: &[CategorizedPr]) -> Vec<(String, Vec<(String, usi
 
 // ── Avatar caching ──────────────────────────────────────────────────────────
 
-pub fn avatars_dir() -> std::path::PathBuf {
-    let dir = crate::state::data_dir().join("avatars");
+/// Avatar cache for `host`. github.com avatars live at the top level; other
+/// hosts get their own subdirectory since logins are only unique per host.
+pub fn avatars_dir(host: &str) -> std::path::PathBuf {
+    let mut dir = crate::state::data_dir().join("avatars");
+    if host != DEFAULT_HOST {
+        dir = dir.join(host);
+    }
     let _ = std::fs::create_dir_all(&dir);
     dir
 }
 
-/// Download missing GitHub avatars for the given authors. Skips already-cached ones.
-/// Downloads the image (GitHub often serves JPEG), decodes it, applies a circular
-/// mask with transparency, and saves as PNG.
-pub fn ensure_avatars(authors: &[String]) {
-    let dir = avatars_dir();
-    for author in authors {
-        let path = dir.join(format!("{author}.png"));
-        if path.exists()
-            && std::fs::metadata(&path)
-                .map(|m| m.len() > 0)
-                .unwrap_or(false)
-        {
-            continue;
-        }
-        let tmp = dir.join(format!("{author}.tmp"));
-        let url = format!("https://github.com/{author}.png?size=64");
-        let ok = Command::new("curl")
-            .args(["-sL", "--max-time", "5", "-o"])
-            .arg(&tmp)
-            .arg(&url)
-            .output()
-            .map(|o| o.status.success())
-            .unwrap_or(false);
-
-        if ok && tmp.exists() {
-            if let Ok(bytes) = std::fs::read(&tmp)
-                && let Ok(img) = image::load_from_memory(&bytes)
-            {
-                let _ = make_circular_png(&img, 64, &path);
-            }
-            let _ = std::fs::remove_file(&tmp);
-        }
-    }
-}
-
 /// Resize an image to `size x size`, apply a circular mask, and save as RGBA PNG.
-fn make_circular_png(img: &image::DynamicImage, size: u32, path: &std::path::Path) -> Result<()> {
+pub(crate) fn make_circular_png(
+    img: &image::DynamicImage,
+    size: u32,
+    path: &std::path::Path,
+) -> Result<()> {
     use image::ImageEncoder;
     use image::codecs::png::PngEncoder;
     use image::{Rgba, RgbaImage};
@@ -499,9 +1341,9 @@ pub fn generate_identicon(username: &str, path: &std::path::Path) -> Result<()>
     Ok(())
 }
 
-/// Generate identicon avatars for demo mode (no network needed).
-pub fn ensure_demo_avatars(authors: &[String]) {
-    let dir = avatars_dir();
+/// Generate identicon avatars for the given authors (no network needed).
+pub fn ensure_identicons(host: &str, authors: &[String]) {
+    let dir = avatars_dir(host);
     for author in authors {
         let path = dir.join(format!("{author}.png"));
         if path.exists()
@@ -536,8 +1378,8 @@ fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (u8, u8, u8) {
 }
 
 /// Get the cached avatar path for a given author, if it exists.
-pub fn avatar_path(author: &str) -> Option<std::path::PathBuf> {
-    let path = avatars_dir().join(format!("{author}.png"));
+pub fn avatar_path(host: &str, author: &str) -> Option<std::path::PathBuf> {
+    let path = avatars_dir(host).join(format!("{author}.png"));
     if path.exists()
         && std::fs::metadata(&path)
             .map(|m| m.len() > 0)
@@ -556,11 +1398,11 @@ pub fn diff_states(
     new_prs: &[CategorizedPr],
 ) -> Vec<Transition> {
     let mut transitions = Vec::new();
-    let new_map: HashMap<&str, &CategorizedPr> =
-        new_prs.iter().map(|pr| (pr.id.as_str(), pr)).collect();
+    let new_map: HashMap<String, &CategorizedPr> =
+        new_prs.iter().map(|pr| (pr.key(), pr)).collect();
 
     for pr in new_prs {
-        match old_prs.get(&pr.id) {
+        match old_prs.get(&pr.key()) {
             None => transitions.push(Transition::New { pr: pr.clone() }),
             Some(old_pr) if old_pr.bucket != pr.bucket => {
                 transitions.push(Transition::Moved {
@@ -568,15 +1410,1096 @@ pub fn diff_states(
                     from: old_pr.bucket,
                 });
             }
+            Some(old_pr) if old_pr.review_state != pr.review_state => {
+                transitions.push(Transition::ReviewStateChanged {
+                    pr: pr.clone(),
+                    from: old_pr.review_state,
+                });
+            }
             _ => {}
         }
+
+        // Known and conflict-free before (not just saved without a merge state)
+        if let Some(old_pr) = old_prs.get(&pr.key())
+            && pr.is_conflicting()
+            && old_pr.mergeable.is_some()
+            && !old_pr.is_conflicting()
+        {
+            transitions.push(Transition::Conflicting { pr: pr.clone() });
+        }
+
+        // Dropped from the queue but still open: checks failed or a conflict
+        if let Some(old_pr) = old_prs.get(&pr.key())
+            && old_pr.merge_queue.is_some()
+            && pr.merge_queue.is_none()
+            && pr.bucket != Bucket::RecentlyMerged
+        {
+            transitions.push(Transition::RemovedFromQueue { pr: pr.clone() });
+        }
+
+        // Had none before (not just saved without a count)
+        if let Some(old_pr) = old_prs.get(&pr.key())
+            && old_pr.unresolved_threads == Some(0)
+            && pr.unresolved_threads.is_some_and(|n| n > 0)
+        {
+            transitions.push(Transition::UnresolvedThreads { pr: pr.clone() });
+        }
     }
 
-    for (id, old_pr) in old_prs {
-        if !new_map.contains_key(id.as_str()) {
+    for (key, old_pr) in old_prs {
+        if !new_map.contains_key(key) {
             transitions.push(Transition::Removed { pr: old_pr.clone() });
         }
     }
 
     transitions
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::FixtureBackend;
+    use serde_json::{Value, json};
+
+    fn viewer() -> Viewer {
+        Viewer {
+            login: "me".to_string(),
+            teams: vec!["acme/platform".to_string()],
+        }
+    }
+
+    /// A PR search node with `fields` merged over the required ones.
+    fn pr_node(number: u32, fields: Value) -> Value {
+        let mut node = json!({
+            "id": format!("PR_{number}"),
+            "number": number,
+            "title": format!("PR {number}"),
+            "url": format!("https://github.com/acme/app/pull/{number}"),
+            "repository": { "nameWithOwner": "acme/app", "defaultBranchRef": { "name": "main" } },
+            "author": { "login": "me" },
+        });
+        node.as_object_mut()
+            .unwrap()
+            .extend(fields.as_object().unwrap().clone());
+        node
+    }
+
+    fn branches(base: &str, head: &str) -> Value {
+        json!({ "baseRefName": base, "headRefName": head, "isCrossRepository": false })
+    }
+
+    fn commit(committed_at: &str, ci: Option<&str>) -> Value {
+        let rollup = ci.map(|state| json!({ "state": state }));
+        json!({ "nodes": [{ "commit": {
+            "oid": "abc123", "committedDate": committed_at, "statusCheckRollup": rollup,
+        } }] })
+    }
+
+    fn review(login: &str, state: &str, submitted_at: &str) -> Value {
+        json!({ "nodes": [{ "author": { "login": login }, "state": state, "submittedAt": submitted_at }] })
+    }
+
+    fn search(nodes: Vec<Value>) -> Value {
+        json!({ "issueCount": nodes.len(), "pageInfo": { "hasNextPage": false }, "nodes": nodes })
+    }
+
+    /// A raw GraphQL response body.
+    fn page(data: Value) -> Vec<u8> {
+        serde_json::to_vec(&json!({ "data": data })).unwrap()
+    }
+
+    /// A first-page response with `searches` filled in and the rest empty.
+    fn response(searches: Value) -> Vec<u8> {
+        let mut data = json!({
+            "needsReview": search(Vec::new()),
+            "authored": search(Vec::new()),
+            "reviewedByMe": search(Vec::new()),
+            "recentlyMerged": search(Vec::new()),
+        });
+        data.as_object_mut()
+            .unwrap()
+            .extend(searches.as_object().unwrap().clone());
+        page(data)
+    }
+
+    fn rate_limit(cost: u32) -> Value {
+        json!({ "cost": cost, "remaining": 4000, "resetAt": "2026-10-16T12:00:00Z" })
+    }
+
+    fn fetch(backend: &FixtureBackend, max: u32) -> Result<GqlResponse> {
+        fetch_prs(backend, 7, max, &[], false, &[])
+    }
+
+    fn categorize(data: &GqlData) -> Vec<CategorizedPr> {
+        categorize_all(data, DEFAULT_HOST, "", &viewer(), &AppConfig::default())
+    }
+
+    fn parse_pr(node: Value) -> PullRequest {
+        serde_json::from_value(node).unwrap()
+    }
+
+    fn parse_data(searches: Value) -> GqlData {
+        serde_json::from_value(searches).unwrap()
+    }
+
+    fn bucket_of(prs: &[CategorizedPr], number: u32) -> Bucket {
+        prs.iter().find(|pr| pr.number == number).unwrap().bucket
+    }
+
+    fn by_key(prs: Vec<CategorizedPr>) -> HashMap<String, CategorizedPr> {
+        prs.into_iter().map(|pr| (pr.key(), pr)).collect()
+    }
+
+    // ── Query building ──────────────────────────────────────────────────────
+
+    #[test]
+    fn build_query_resumes_after_cursor() {
+        let specs = search_specs("2026-10-01", &[], false, &[], true);
+        let query = build_query(&[(&specs[0], None, 50), (&specs[1], Some("Y3Vyc29y"), 20)]);
+        assert!(query.contains(
+            r#"needsReview: search(query: "is:pr is:open review-requested:@me", type: ISSUE, first: 50)"#
+        ));
+        assert!(query.contains(
+            r#"authored: search(query: "is:pr is:open author:@me", type: ISSUE, first: 20, after: "Y3Vyc29y")"#
+        ));
+        assert_eq!(
+            query.matches("pageInfo { hasNextPage endCursor }").count(),
+            2
+        );
+    }
+
+    #[test]
+    fn search_specific_fields_stay_off_other_searches() {
+        let saved = [SavedSearch {
+            name: "Infra".to_string(),
+            query: "label:infra".to_string(),
+        }];
+        let owners = [
+            ("mergeQueueEntry", "authored"),
+            ("autoMergeRequest", "authored"),
+            ("mergeStateStatus", "authored"),
+            ("reviewThreads", "authored"),
+            ("contexts(first: 25)", "authored"),
+            ("reviewRequests", "needsReview"),
+            ("timelineItems", "needsReview"),
+        ];
+        let specs = search_specs("2026-10-01", &saved, false, &["alice".to_string()], true);
+        for spec in &specs {
+            let field = search_field(spec, None, 50);
+            for (name, owner) in owners {
+                assert_eq!(
+                    field.contains(name),
+                    spec.alias == owner,
+                    "{name} in {}",
+                    spec.alias
+                );
+            }
+        }
+
+        let specs = search_specs("2026-10-01", &[], false, &[], false);
+        let authored = search_field(&specs[1], None, 50);
+        assert!(!authored.contains("mergeQueueEntry"));
+        assert!(authored.contains("autoMergeRequest"));
+    }
+
+    #[test]
+    fn build_query_escapes_saved_search_quotes() {
+        let saved = [SavedSearch {
+            name: "Review".to_string(),
+            query: r#"label:"needs review""#.to_string(),
+        }];
+        let specs = search_specs("2026-10-01", &saved, false, &[], true);
+        let query = build_query(&[(&specs[4], None, 50)]);
+        assert!(query.contains(
+            r#"saved0: search(query: "is:pr label:\"needs review\"", type: ISSUE, first: 50)"#
+        ));
+    }
+
+    #[test]
+    fn team_queries_stay_under_length_limit() {
+        let logins: Vec<String> = (0..60).map(|i| format!("developer-{i}")).collect();
+        let queries = team_search_queries(&logins);
+        assert!(queries.len() > 1);
+        assert!(queries.iter().all(|q| q.len() <= MAX_SEARCH_QUERY_LEN));
+        let authors = queries
+            .iter()
+            .flat_map(|q| q.split(' '))
+            .filter(|t| t.starts_with("author:"));
+        assert_eq!(authors.count(), logins.len());
+        assert!(team_search_queries(&[]).is_empty());
+    }
+
+    // ── Fetching ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn fetch_prs_retries_without_merge_queue_fields() {
+        let unsupported = serde_json::to_vec(&json!({ "errors": [{
+            "message": "Field 'mergeQueueEntry' doesn't exist on type 'PullRequest'",
+        }] }))
+        .unwrap();
+        let backend = FixtureBackend::new(vec![
+            unsupported,
+            response(json!({ "authored": search(vec![pr_node(1, json!({}))]) })),
+        ]);
+        let response = fetch(&backend, 50).unwrap();
+        assert_eq!(response.data.authored.nodes.len(), 1);
+        assert!(response.errors.is_empty());
+    }
+
+    #[test]
+    fn fetch_prs_follows_cursors_and_sums_cost() {
+        let backend = FixtureBackend::new(vec![
+            response(json!({
+                "authored": {
+                    "issueCount": 3,
+                    "pageInfo": { "hasNextPage": true, "endCursor": "c1" },
+                    "nodes": [pr_node(1, json!({})), pr_node(2, json!({}))],
+                },
+                "rateLimit": rate_limit(1),
+            })),
+            page(json!({
+                "authored": {
+                    "issueCount": 3,
+                    "pageInfo": { "hasNextPage": false, "endCursor": "c2" },
+                    "nodes": [pr_node(3, json!({}))],
+                },
+                "rateLimit": rate_limit(1),
+            })),
+        ]);
+        let response = fetch(&backend, 100).unwrap();
+        let numbers: Vec<u32> = response
+            .data
+            .authored
+            .nodes
+            .iter()
+            .map(|pr| pr.number)
+            .collect();
+        assert_eq!(numbers, [1, 2, 3]);
+        assert_eq!(response.data.rate_limit.unwrap().cost, 2);
+    }
+
+    #[test]
+    fn fetch_prs_stops_at_cap() {
+        // A second request would fail to parse
+        let backend = FixtureBackend::new(vec![
+            response(json!({
+                "authored": {
+                    "issueCount": 500,
+                    "pageInfo": { "hasNextPage": true, "endCursor": "c1" },
+                    "nodes": [pr_node(1, json!({})), pr_node(2, json!({}))],
+                },
+            })),
+            b"not json".to_vec(),
+        ]);
+        let response = fetch(&backend, 2).unwrap();
+        assert_eq!(response.data.authored.nodes.len(), 2);
+        assert!(response.data.authored.is_truncated());
+    }
+
+    #[test]
+    fn fetch_prs_keeps_partial_data_with_errors() {
+        let body = json!({
+            "data": {
+                "needsReview": search(vec![pr_node(1, json!({})), Value::Null]),
+                "authored": null,
+            },
+            "errors": [{
+                "type": "FORBIDDEN",
+                "message": "Resource protected by organization SAML enforcement. You must grant your Personal Access token access to the `acme` organization.",
+                "extensions": { "saml_failure": true },
+            }],
+        });
+        let backend = FixtureBackend::new(vec![serde_json::to_vec(&body).unwrap()]);
+        let response = fetch(&backend, 100).unwrap();
+        assert_eq!(response.data.needs_review.nodes.len(), 1);
+        assert!(response.data.authored.nodes.is_empty());
+        assert_eq!(
+            response.api_errors(),
+            [ApiError::SamlRequired {
+                org: Some("acme".to_string())
+            }]
+        );
+    }
+
+    #[test]
+    fn fetch_prs_fails_without_data() {
+        let body = json!({ "data": null, "errors": [{ "type": "RATE_LIMITED", "message": "API rate limit exceeded" }] });
+        let backend = FixtureBackend::new(vec![serde_json::to_vec(&body).unwrap()]);
+        let err = fetch(&backend, 100).unwrap_err();
+        assert_eq!(err.downcast_ref::<ApiError>(), Some(&ApiError::RateLimited));
+    }
+
+    #[test]
+    fn fetch_prs_keeps_earlier_pages_when_a_later_one_fails() {
+        let backend = FixtureBackend::new(vec![
+            response(json!({
+                "authored": {
+                    "issueCount": 3,
+                    "pageInfo": { "hasNextPage": true, "endCursor": "c1" },
+                    "nodes": [pr_node(1, json!({})), pr_node(2, json!({}))],
+                },
+            })),
+            serde_json::to_vec(&json!({ "data": null, "errors": [{ "message": "timeout" }] }))
+                .unwrap(),
+        ]);
+        let response = fetch(&backend, 100).unwrap();
+        assert_eq!(response.data.authored.nodes.len(), 2);
+        assert_eq!(
+            response.api_errors(),
+            [ApiError::Other {
+                message: "timeout".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn check_saved_search_reports_count_and_cost() {
+        let search = SavedSearch {
+            name: "Deploys".to_string(),
+            query: "label:deploy".to_string(),
+        };
+        let backend = FixtureBackend::new(vec![
+            page(json!({ "check": { "issueCount": 12 } })),
+            page(json!({ "rateLimit": { "cost": 3 } })),
+        ]);
+        let check = check_saved_search(&backend, &search, 100).unwrap();
+        assert_eq!((check.issue_count, check.cost), (12, 3));
+
+        let invalid = serde_json::to_vec(&json!({
+            "data": { "check": null },
+            "errors": [{ "message": "Invalid search query" }],
+        }))
+        .unwrap();
+        let backend = FixtureBackend::new(vec![invalid]);
+        let err = check_saved_search(&backend, &search, 100).unwrap_err();
+        assert!(err.to_string().contains("Invalid search query"));
+
+        // Issue searches are refused before asking GitHub
+        let issues = SavedSearch {
+            query: "is:issue label:deploy".to_string(),
+            ..search
+        };
+        let err = check_saved_search(&FixtureBackend::new(Vec::new()), &issues, 100).unwrap_err();
+        assert!(err.to_string().contains("only show pull requests"));
+    }
+
+    // ── Categorization ──────────────────────────────────────────────────────
+
+    #[test]
+    fn review_requests_split_direct_and_team() {
+        let requests = |reviewers: Value| json!({ "reviewRequests": { "nodes": reviewers } });
+        let prs = categorize(&parse_data(json!({
+            "needsReview": search(vec![
+                pr_node(1, requests(json!([{ "requestedReviewer": { "login": "me" } }]))),
+                // The viewer's team wins over another requested team
+                pr_node(2, requests(json!([
+                    { "requestedReviewer": { "name": "Web", "combinedSlug": "acme/web" } },
+                    { "requestedReviewer": { "name": "Platform", "combinedSlug": "acme/platform" } },
+                ]))),
+                // Direct and team: the direct request wins
+                pr_node(3, requests(json!([
+                    { "requestedReviewer": { "name": "Platform", "combinedSlug": "acme/platform" } },
+                    { "requestedReviewer": { "login": "me" } },
+                ]))),
+                // No requests visible (e.g. already answered)
+                pr_node(4, json!({})),
+                // Only a team the viewer isn't known to be on
+                pr_node(5, requests(json!([
+                    { "requestedReviewer": { "name": "Web", "combinedSlug": "acme/web" } },
+                ]))),
+            ]),
+        })));
+        assert_eq!(bucket_of(&prs, 1), Bucket::NeedsYourReview);
+        assert_eq!(bucket_of(&prs, 2), Bucket::TeamReviewRequested);
+        assert_eq!(prs[1].team.as_deref(), Some("Platform"));
+        assert_eq!(bucket_of(&prs, 3), Bucket::NeedsYourReview);
+        assert_eq!(bucket_of(&prs, 4), Bucket::NeedsYourReview);
+        assert_eq!(bucket_of(&prs, 5), Bucket::NeedsYourReview);
+        assert_eq!(prs[4].team, None);
+
+        let new = Transition::New { pr: prs[1].clone() };
+        assert_eq!(
+            new.notification_text(),
+            Some((
+                "Team Review Requested",
+                "Platform: #2 PR 2 (app)".to_string()
+            ))
+        );
+        let narrowed = Transition::Moved {
+            pr: prs[1].clone(),
+            from: Bucket::NeedsYourReview,
+        };
+        assert_eq!(narrowed.notification_text(), None);
+    }
+
+    #[test]
+    fn authored_prs_bucket_by_draft_ci_and_decision() {
+        let mut failing = commit("2026-09-30T00:00:00Z", Some("FAILURE"));
+        failing["nodes"][0]["commit"]["statusCheckRollup"]["contexts"] = json!({ "nodes": [
+            { "name": "lint", "conclusion": "SUCCESS", "detailsUrl": "https://ci/1" },
+            { "name": "test", "conclusion": "FAILURE", "detailsUrl": "https://ci/2" },
+            { "context": "ci/legacy", "state": "ERROR", "targetUrl": "https://ci/3" },
+        ] });
+        let prs = categorize(&parse_data(json!({
+            "authored": search(vec![
+                pr_node(1, json!({ "isDraft": true, "commits": failing })),
+                pr_node(2, json!({ "reviewDecision": "APPROVED", "commits": failing })),
+                pr_node(3, json!({ "reviewDecision": "APPROVED" })),
+                pr_node(4, json!({ "reviewDecision": "CHANGES_REQUESTED" })),
+                pr_node(5, json!({})),
+                pr_node(6, json!({ "reviewDecision": "CHANGES_REQUESTED", "commits": failing })),
+            ]),
+        })));
+        assert_eq!(bucket_of(&prs, 1), Bucket::Drafts);
+        assert_eq!(bucket_of(&prs, 2), Bucket::CiFailing);
+        assert_eq!(bucket_of(&prs, 3), Bucket::Approved);
+        assert_eq!(bucket_of(&prs, 4), Bucket::ReturnedToYou);
+        assert_eq!(bucket_of(&prs, 5), Bucket::WaitingForReviewers);
+        assert_eq!(bucket_of(&prs, 6), Bucket::ReturnedToYou);
+
+        let ci_failing = &prs[1];
+        assert_eq!(
+            ci_failing.failing_checks,
+            [
+                FailingCheck {
+                    name: "test".to_string(),
+                    conclusion: "FAILURE".to_string(),
+                    details_url: Some("https://ci/2".to_string()),
+                },
+                FailingCheck {
+                    name: "ci/legacy".to_string(),
+                    conclusion: "ERROR".to_string(),
+                    details_url: Some("https://ci/3".to_string()),
+                },
+            ]
+        );
+        let new = Transition::New {
+            pr: ci_failing.clone(),
+        };
+        assert_eq!(
+            new.notification_text(),
+            Some(("CI Failing", "#2 PR 2 (app): test, ci/legacy".to_string()))
+        );
+    }
+
+    #[test]
+    fn bot_reviews_alone_do_not_decide() {
+        let prs = categorize(&parse_data(json!({
+            "authored": search(vec![
+                pr_node(1, json!({
+                    "reviewDecision": "APPROVED",
+                    "latestReviews": review("renovate[bot]", "APPROVED", "2026-10-01T00:00:00Z"),
+                })),
+                pr_node(2, json!({
+                    "reviewDecision": "CHANGES_REQUESTED",
+                    "latestReviews": review("dependabot", "CHANGES_REQUESTED", "2026-10-01T00:00:00Z"),
+                })),
+                pr_node(3, json!({
+                    "reviewDecision": "APPROVED",
+                    "latestReviews": { "nodes": [
+                        { "author": { "login": "renovate[bot]" }, "state": "APPROVED", "submittedAt": "2026-10-01T00:00:00Z" },
+                        { "author": { "login": "alice" }, "state": "APPROVED", "submittedAt": "2026-10-01T00:00:00Z" },
+                    ] },
+                })),
+            ]),
+        })));
+        assert_eq!(bucket_of(&prs, 1), Bucket::WaitingForReviewers);
+        assert_eq!(bucket_of(&prs, 2), Bucket::WaitingForReviewers);
+        assert_eq!(bucket_of(&prs, 3), Bucket::Approved);
+    }
+
+    #[test]
+    fn reviewed_prs_split_by_your_review_and_dedupe() {
+        let prs = categorize(&parse_data(json!({
+            "needsReview": search(vec![pr_node(1, json!({}))]),
+            "reviewedByMe": search(vec![
+                // Already listed as a review request
+                pr_node(1, json!({})),
+                pr_node(3, json!({ "latestReviews": review("me", "APPROVED", "2026-10-01T00:00:00Z") })),
+                pr_node(4, json!({
+                    "latestReviews": review("me", "CHANGES_REQUESTED", "2026-10-01T00:00:00Z"),
+                    "commits": commit("2026-10-02T00:00:00Z", None),
+                })),
+                pr_node(5, json!({
+                    "latestReviews": review("me", "CHANGES_REQUESTED", "2026-10-01T00:00:00Z"),
+                    "commits": commit("2026-09-30T00:00:00Z", None),
+                })),
+                pr_node(6, json!({ "latestReviews": review("me", "COMMENTED", "2026-10-01T00:00:00Z") })),
+                // Someone else still blocks it
+                pr_node(7, json!({
+                    "reviewDecision": "CHANGES_REQUESTED",
+                    "latestReviews": review("me", "COMMENTED", "2026-10-01T00:00:00Z"),
+                })),
+            ]),
+        })));
+        assert_eq!(prs.len(), 6);
+        assert_eq!(bucket_of(&prs, 1), Bucket::NeedsYourReview);
+        assert_eq!(bucket_of(&prs, 3), Bucket::ApprovedByYou);
+        assert_eq!(bucket_of(&prs, 4), Bucket::UpdatedSinceYourReview);
+        assert_eq!(bucket_of(&prs, 5), Bucket::WaitingForAuthor);
+        assert_eq!(bucket_of(&prs, 6), Bucket::CommentedByYou);
+        assert_eq!(bucket_of(&prs, 7), Bucket::WaitingForAuthor);
+
+        let moved = |number, from| Transition::Moved {
+            pr: prs.iter().find(|pr| pr.number == number).unwrap().clone(),
+            from,
+        };
+        assert_eq!(
+            moved(3, Bucket::NeedsYourReview).notification_text(),
+            Some(("Approved by You", "#3 PR 3 (app)".to_string()))
+        );
+        assert_eq!(
+            moved(6, Bucket::UpdatedSinceYourReview).notification_text(),
+            Some(("Commented by You", "#6 PR 6 (app)".to_string()))
+        );
+    }
+
+    #[test]
+    fn review_sub_states() {
+        let reviewed = review("me", "COMMENTED", "2026-10-01T00:00:00Z");
+        let never = parse_pr(pr_node(1, json!({})));
+        assert_eq!(
+            review_sub_state(&never, &viewer()),
+            ReviewSubState::NeverReviewed
+        );
+
+        let pushed = parse_pr(pr_node(
+            2,
+            json!({
+                "latestReviews": reviewed,
+                "commits": commit("2026-10-02T00:00:00Z", None),
+            }),
+        ));
+        assert_eq!(
+            review_sub_state(&pushed, &viewer()),
+            ReviewSubState::NewCommits
+        );
+
+        // A re-request through the viewer's team wins over new commits
+        let re_requested = parse_pr(pr_node(
+            3,
+            json!({
+                "latestReviews": reviewed,
+                "commits": commit("2026-10-02T00:00:00Z", None),
+                "timelineItems": { "nodes": [{
+                    "createdAt": "2026-10-03T00:00:00Z",
+                    "requestedReviewer": { "combinedSlug": "acme/platform" },
+                }] },
+            }),
+        ));
+        assert_eq!(
+            review_sub_state(&re_requested, &viewer()),
+            ReviewSubState::ReRequested
+        );
+
+        // An older request doesn't count
+        let stale_request = parse_pr(pr_node(
+            4,
+            json!({
+                "latestReviews": reviewed,
+                "commits": commit("2026-10-02T00:00:00Z", None),
+                "timelineItems": { "nodes": [{
+                    "createdAt": "2026-09-01T00:00:00Z",
+                    "requestedReviewer": { "login": "me" },
+                }] },
+            }),
+        ));
+        assert_eq!(
+            review_sub_state(&stale_request, &viewer()),
+            ReviewSubState::NewCommits
+        );
+
+        // Still requested, but the event is past the timeline window
+        let still_requested = parse_pr(pr_node(
+            5,
+            json!({
+                "latestReviews": reviewed,
+                "reviewRequests": { "nodes": [{ "requestedReviewer": { "login": "me" } }] },
+            }),
+        ));
+        assert_eq!(
+            review_sub_state(&still_requested, &viewer()),
+            ReviewSubState::ReRequested
+        );
+
+        // Reviewed with no request pending: not a re-request
+        let answered = parse_pr(pr_node(6, json!({ "latestReviews": reviewed })));
+        assert_eq!(
+            review_sub_state(&answered, &viewer()),
+            ReviewSubState::NeverReviewed
+        );
+    }
+
+    #[test]
+    fn review_state_changes_notify_only_with_a_known_previous_state() {
+        let reviewed = review("me", "COMMENTED", "2026-10-01T00:00:00Z");
+        let old = categorize(&parse_data(json!({
+            "needsReview": search(vec![
+                pr_node(1, json!({ "latestReviews": reviewed })),
+            ]),
+        })));
+        let new = categorize(&parse_data(json!({
+            "needsReview": search(vec![pr_node(1, json!({
+                "latestReviews": reviewed,
+                "commits": commit("2026-10-02T00:00:00Z", None),
+            }))]),
+        })));
+
+        let transitions = diff_states(&by_key(old.clone()), &new);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(
+            transitions[0].notification_text(),
+            Some(("New Commits Since Your Review", "#1 PR 1 (app)".to_string()))
+        );
+
+        // State saved before sub-states existed
+        let mut legacy = old;
+        legacy[0].review_state = None;
+        let transitions = diff_states(&by_key(legacy), &new);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].notification_text(), None);
+    }
+
+    #[test]
+    fn saved_searches_get_their_own_sections() {
+        let config = AppConfig {
+            saved_searches: vec![SavedSearch {
+                name: "Deploys".to_string(),
+                query: "label:deploy".to_string(),
+            }],
+            ..AppConfig::default()
+        };
+        let data = parse_data(json!({
+            "authored": search(vec![pr_node(1, json!({}))]),
+            // Also listed as authored; a repeat within the search is dropped
+            "saved0": search(vec![pr_node(1, json!({})), pr_node(2, json!({})), pr_node(2, json!({}))]),
+        }));
+        let prs = categorize_all(&data, DEFAULT_HOST, "", &viewer(), &config);
+        let sections: Vec<(u32, &str)> =
+            prs.iter().map(|pr| (pr.number, pr.section_id())).collect();
+        assert_eq!(
+            sections,
+            [
+                (1, "waiting_for_reviewers"),
+                (1, "search_deploys"),
+                (2, "search_deploys"),
+            ]
+        );
+        assert_eq!(prs[1].key(), "PR_1#search_deploys");
+    }
+
+    // ── Stacked PRs ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn links_and_orders_stacks() {
+        let mut fork = branches("feat-c", "feat-d");
+        fork["isCrossRepository"] = json!(true);
+        let prs = categorize(&parse_data(json!({
+            "authored": search(vec![
+                pr_node(12, branches("feat-b", "feat-c")),
+                pr_node(10, branches("main", "feat-a")),
+                pr_node(13, branches("main", "unrelated")),
+                pr_node(11, branches("feat-a", "feat-b")),
+                // A fork's head branch isn't this repo's feat-d
+                pr_node(14, fork),
+                pr_node(15, branches("feat-d", "feat-e")),
+            ]),
+        })));
+        let stacked_on = |number| {
+            prs.iter()
+                .find(|pr| pr.number == number)
+                .unwrap()
+                .stacked_on
+        };
+        assert_eq!(stacked_on(10), None);
+        assert_eq!(stacked_on(11), Some(10));
+        assert_eq!(stacked_on(12), Some(11));
+        assert_eq!(stacked_on(14), Some(12));
+        assert_eq!(stacked_on(15), None);
+
+        let rows: Vec<&CategorizedPr> = prs.iter().collect();
+        let order: Vec<(u32, usize)> = stack_order(&rows)
+            .into_iter()
+            .map(|(pr, depth)| (pr.number, depth))
+            .collect();
+        assert_eq!(
+            order,
+            [(10, 0), (11, 1), (12, 2), (14, 3), (13, 0), (15, 0)]
+        );
+    }
+
+    #[test]
+    fn default_branch_heads_are_not_stack_parents() {
+        let prs = categorize(&parse_data(json!({
+            "authored": search(vec![
+                // Release PR from the default branch
+                pr_node(1, branches("production", "main")),
+                pr_node(2, branches("main", "feature")),
+            ]),
+        })));
+        assert!(prs.iter().all(|pr| pr.stacked_on.is_none()));
+    }
+
+    // ── Filtering ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn filter_prs_hides_bot_prs_per_bucket() {
+        let mut node = pr_node(2, json!({}));
+        node["author"] = json!({ "login": "dependabot" });
+        let mut authored_by_bot = node.clone();
+        authored_by_bot["id"] = json!("PR_3");
+        authored_by_bot["number"] = json!(3);
+        let prs = categorize(&parse_data(json!({
+            "needsReview": search(vec![pr_node(1, json!({})), node]),
+            "authored": search(vec![authored_by_bot]),
+        })));
+        let config = AppConfig {
+            bot_prs: HashMap::from([
+                ("needs_your_review".to_string(), BotPrHandling::Hide),
+                ("waiting_for_reviewers".to_string(), BotPrHandling::Collapse),
+            ]),
+            ..AppConfig::default()
+        };
+        let numbers: Vec<u32> = filter_prs(prs, &config)
+            .iter()
+            .map(|pr| pr.number)
+            .collect();
+        assert_eq!(numbers, [1, 3]);
+    }
+
+    #[test]
+    fn filter_prs_collapses_or_hides_stale_prs() {
+        let days_ago = |days| (Utc::now() - Duration::days(days)).to_rfc3339();
+        let mut other_repo = pr_node(4, json!({ "updatedAt": days_ago(30) }));
+        other_repo["repository"]["nameWithOwner"] = json!("acme/docs");
+        let prs = categorize(&parse_data(json!({
+            "authored": search(vec![
+                pr_node(1, json!({ "updatedAt": days_ago(2) })),
+                pr_node(2, json!({ "updatedAt": days_ago(30) })),
+                other_repo,
+            ]),
+            "recentlyMerged": search(vec![pr_node(3, json!({
+                "updatedAt": days_ago(30),
+                "mergedAt": days_ago(30),
+            }))]),
+        })));
+        let mut config = AppConfig {
+            stale_after_days: Some(14),
+            // Never stale
+            repo_stale_after_days: HashMap::from([("acme/docs".to_string(), 0)]),
+            ..AppConfig::default()
+        };
+
+        let collapsed = filter_prs(prs.clone(), &config);
+        assert_eq!(bucket_of(&collapsed, 1), Bucket::WaitingForReviewers);
+        assert_eq!(bucket_of(&collapsed, 2), Bucket::Stale);
+        assert_eq!(bucket_of(&collapsed, 3), Bucket::RecentlyMerged);
+        assert_eq!(bucket_of(&collapsed, 4), Bucket::WaitingForReviewers);
+
+        config.stale_prs = StalePrHandling::Hide;
+        let numbers: Vec<u32> = filter_prs(prs, &config)
+            .iter()
+            .map(|pr| pr.number)
+            .collect();
+        assert_eq!(numbers, [1, 4, 3]);
+    }
+
+    // ── State diffing ───────────────────────────────────────────────────────
+
+    #[test]
+    fn diff_states_reports_new_conflicts() {
+        let old = categorize(&parse_data(json!({
+            "authored": search(vec![
+                pr_node(1, json!({ "mergeable": "MERGEABLE" })),
+                pr_node(2, json!({ "mergeable": "CONFLICTING" })),
+                // Saved before merge states were tracked
+                pr_node(3, json!({})),
+            ]),
+        })));
+        let new = categorize(&parse_data(json!({
+            "authored": search(vec![
+                pr_node(1, json!({ "mergeable": "CONFLICTING", "mergeStateStatus": "DIRTY" })),
+                pr_node(2, json!({ "mergeable": "CONFLICTING" })),
+                pr_node(3, json!({ "mergeable": "CONFLICTING" })),
+            ]),
+        })));
+        let transitions = diff_states(&by_key(old), &new);
+        assert_eq!(transitions.len(), 1);
+        assert!(matches!(&transitions[0], Transition::Conflicting { pr } if pr.number == 1));
+        assert_eq!(
+            transitions[0].notification_text(),
+            Some(("Merge Conflict", "#1 PR 1 (app)".to_string()))
+        );
+        assert_eq!(new[0].merge_marker(), Some("⚠ conflicts"));
+    }
+
+    #[test]
+    fn leaving_the_merge_queue_unmerged_is_reported() {
+        let queued = json!({ "mergeQueueEntry": { "position": 3, "state": "QUEUED" } });
+        let old = categorize(&parse_data(json!({
+            "authored": search(vec![pr_node(1, queued.clone()), pr_node(2, queued)]),
+        })));
+        assert_eq!(old[0].queue_marker().as_deref(), Some("queued #3"));
+
+        let new = categorize(&parse_data(json!({
+            "authored": search(vec![
+                pr_node(1, json!({ "autoMergeRequest": { "enabledAt": "2026-10-01T00:00:00Z" } })),
+            ]),
+            "recentlyMerged": search(vec![pr_node(2, json!({ "mergedAt": "2026-10-02T00:00:00Z" }))]),
+        })));
+        assert_eq!(new[0].queue_marker().as_deref(), Some("auto-merge on"));
+
+        let removed: Vec<u32> = diff_states(&by_key(old), &new)
+            .iter()
+            .filter_map(|t| match t {
+                Transition::RemovedFromQueue { pr } => Some(pr.number),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(removed, [1]);
+    }
+
+    #[test]
+    fn new_unresolved_threads_are_reported() {
+        let threads = |total: u32, unresolved: u32| {
+            let nodes: Vec<Value> = (0..total)
+                .map(|i| json!({ "isResolved": i >= unresolved }))
+                .collect();
+            json!({ "reviewThreads": { "totalCount": total, "nodes": nodes } })
+        };
+        let old = categorize(&parse_data(json!({
+            "authored": search(vec![pr_node(1, threads(1, 0)), pr_node(2, threads(2, 1)), pr_node(3, json!({}))]),
+        })));
+        let new = categorize(&parse_data(json!({
+            "authored": search(vec![pr_node(1, threads(3, 2)), pr_node(2, threads(3, 2)), pr_node(3, threads(1, 1))]),
+        })));
+        assert_eq!(new[0].thread_marker().as_deref(), Some("2/3 unresolved"));
+        assert_eq!(old[2].thread_marker(), None);
+
+        let transitions = diff_states(&by_key(old), &new);
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(
+            transitions[0].notification_text(),
+            Some((
+                "Unresolved Review Threads",
+                "#1 PR 1 (app): 2 unresolved threads".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn merge_states_keep_the_nodes_that_resolved() {
+        let unknown = |number| CategorizedPr {
+            mergeable: Some("UNKNOWN".to_string()),
+            ..make_pr(
+                &parse_pr(pr_node(number, json!({}))),
+                "github.com",
+                Bucket::WaitingForReviewers,
+            )
+        };
+        let mut prs = vec![unknown(1), unknown(2)];
+        let backend = FixtureBackend::new(vec![
+            serde_json::to_vec(&json!({
+                "data": { "nodes": [
+                    { "id": "PR_1", "mergeable": "CONFLICTING", "mergeStateStatus": "DIRTY" },
+                    null,
+                ] },
+                "errors": [{ "type": "FORBIDDEN", "message": "Resource not accessible" }],
+            }))
+            .unwrap(),
+        ]);
+        refresh_merge_states(&backend, &mut prs).unwrap();
+        assert_eq!(prs[0].mergeable.as_deref(), Some("CONFLICTING"));
+        assert_eq!(prs[0].merge_state_status.as_deref(), Some("DIRTY"));
+        assert!(prs[1].merge_state_unknown());
+    }
+
+    // ── Team view ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn team_logins_merge_members_and_extras() {
+        let backend = FixtureBackend::new(vec![page(json!({
+            "organization": { "team": { "members": { "nodes": [
+                { "login": "olivia" }, { "login": "James" },
+            ] } } },
+        }))]);
+        let config = AppConfig {
+            team_slug: Some("acme/platform".to_string()),
+            team_members: vec!["james".to_string(), "zoe".to_string()],
+            ..AppConfig::default()
+        };
+        assert_eq!(
+            team_logins(&backend, &config).unwrap(),
+            ["james", "olivia", "zoe"]
+        );
+
+        let backend = FixtureBackend::new(vec![page(json!({ "organization": { "team": null } }))]);
+        assert!(get_team_members(&backend, "acme/nope").is_err());
+        assert!(get_team_members(&backend, "no-slash").is_err());
+        let err = get_team_members(&backend, r#"acme/x") { id } #"#).unwrap_err();
+        assert!(err.to_string().contains("not a valid team slug"), "{err}");
+    }
+
+    #[test]
+    fn viewer_teams_take_plain_logins_only() {
+        let backend = FixtureBackend::new(vec![page(json!({
+            "viewer": { "organizations": { "nodes": [
+                { "teams": { "nodes": [{ "combinedSlug": "acme/platform" }] } },
+                { "teams": { "nodes": [] } },
+            ] } },
+        }))]);
+        assert_eq!(get_viewer_teams(&backend, "me").unwrap(), ["acme/platform"]);
+        assert!(get_viewer_teams(&backend, r#"me"]) { id } #"#).is_err());
+    }
+
+    #[test]
+    fn team_prs_are_listed_apart_from_your_own() {
+        let mut teammate = pr_node(2, json!({ "reviewDecision": "APPROVED" }));
+        teammate["author"] = json!({ "login": "olivia" });
+        let prs = categorize(&parse_data(json!({
+            "needsReview": search(vec![teammate.clone()]),
+            "team0": search(vec![teammate.clone()]),
+            "team1": search(vec![teammate]),
+        })));
+        let sections: Vec<(u32, &str)> =
+            prs.iter().map(|pr| (pr.number, pr.section_id())).collect();
+        assert_eq!(sections, [(2, "needs_your_review"), (2, TEAM_SECTION_ID)]);
+        assert_eq!(prs[1].bucket, Bucket::Approved);
+    }
+
+    // ── Issues ──────────────────────────────────────────────────────────────
+
+    fn issue_node(number: u32) -> Value {
+        json!({
+            "id": format!("I_{number}"),
+            "number": number,
+            "title": format!("Issue {number}"),
+            "url": format!("https://github.com/acme/app/issues/{number}"),
+            "repository": { "nameWithOwner": "acme/app" },
+            "author": { "login": "alice" },
+            "labels": { "nodes": [{ "name": "bug", "color": "d73a4a" }] },
+        })
+    }
+
+    #[test]
+    fn issues_are_paged_and_bucketed() {
+        let backend = FixtureBackend::new(vec![
+            response(json!({
+                "assignedIssues": {
+                    "issueCount": 2,
+                    "pageInfo": { "hasNextPage": true, "endCursor": "c1" },
+                    "nodes": [issue_node(1)],
+                },
+                "mentionedIssues": search(vec![issue_node(1), issue_node(3)]),
+            })),
+            page(json!({ "assignedIssues": search(vec![issue_node(2)]) })),
+        ]);
+        let response = fetch_prs(&backend, 7, 100, &[], true, &[]).unwrap();
+        assert_eq!(response.data.assigned_issues.nodes.len(), 2);
+
+        let prs = categorize(&response.data);
+        let buckets: Vec<(u32, Bucket)> = prs.iter().map(|pr| (pr.number, pr.bucket)).collect();
+        // Assigned wins over mentioned
+        assert_eq!(
+            buckets,
+            [
+                (1, Bucket::AssignedIssues),
+                (2, Bucket::AssignedIssues),
+                (3, Bucket::MentionedIssues),
+            ]
+        );
+        assert_eq!(prs[0].labels[0].name, "bug");
+        let new = Transition::New { pr: prs[2].clone() };
+        assert_eq!(
+            new.notification_text(),
+            Some(("Mentioned in Issue", "#3 Issue 3 (app)".to_string()))
+        );
+    }
+
+    // ── Notifications ───────────────────────────────────────────────────────
+
+    #[test]
+    fn notifications_become_rows_linking_to_the_web() {
+        let notification = |id: &str, subject: Value| {
+            json!({
+                "id": id,
+                "reason": "mention",
+                "updated_at": "2026-10-15T10:00:00Z",
+                "subject": subject,
+                "repository": { "full_name": "acme/app", "html_url": "https://ghe.acme.com/acme/app" },
+            })
+        };
+        let notifications: Vec<Notification> = serde_json::from_value(json!([
+            notification(
+                "1",
+                json!({
+                    "title": "Fix login",
+                    "url": "https://ghe.acme.com/api/v3/repos/acme/app/pulls/42",
+                    "type": "PullRequest",
+                })
+            ),
+            notification(
+                "2",
+                json!({ "title": "Q3 planning", "url": null, "type": "Discussion" })
+            ),
+        ]))
+        .unwrap();
+
+        let rows = categorize_notifications(&notifications, "ghe.acme.com", "work");
+        assert_eq!(rows[0].id, "1");
+        assert_eq!(rows[0].number, 42);
+        assert_eq!(rows[0].url, "https://ghe.acme.com/acme/app/pull/42");
+        assert_eq!(rows[0].reason.as_deref(), Some("mention"));
+        assert_eq!(rows[0].bucket, Bucket::Notifications);
+        assert_eq!(rows[1].number, 0);
+        assert_eq!(rows[1].url, "https://ghe.acme.com/acme/app");
+    }
+
+    // ── End to end ──────────────────────────────────────────────────────────
+
+    /// Two recorded polls through fetch, categorize and diff: what changed
+    /// between them is what the user is notified about.
+    #[test]
+    fn replayed_polls_notify_on_changes() {
+        let backend = FixtureBackend::new(vec![
+            response(json!({
+                "needsReview": search(vec![pr_node(1, json!({}))]),
+                "authored": search(vec![pr_node(2, json!({})), pr_node(3, json!({}))]),
+            })),
+            response(json!({
+                "authored": search(vec![pr_node(2, json!({ "reviewDecision": "APPROVED" }))]),
+                "recentlyMerged": search(vec![pr_node(3, json!({ "mergedAt": "2026-10-01T00:00:00Z" }))]),
+            })),
+        ]);
+        let first = categorize(&fetch(&backend, 100).unwrap().data);
+        let buckets: Vec<Bucket> = first.iter().map(|pr| pr.bucket).collect();
+        assert_eq!(
+            buckets,
+            [
+                Bucket::NeedsYourReview,
+                Bucket::WaitingForReviewers,
+                Bucket::WaitingForReviewers
+            ]
+        );
+
+        let second = categorize(&fetch(&backend, 100).unwrap().data);
+        let transitions = diff_states(&by_key(first), &second);
+        assert_eq!(transitions.len(), 3);
+        assert!(
+            transitions
+                .iter()
+                .any(|t| matches!(t, Transition::Removed { pr } if pr.number == 1))
+        );
+        let notifications: Vec<(&str, String)> = transitions
+            .iter()
+            .filter_map(Transition::notification_text)
+            .collect();
+        assert_eq!(
+            notifications,
+            [
+                ("PR Approved", "#2 PR 2 (app)".to_string()),
+                ("PR Merged", "#3 PR 3 (app)".to_string()),
+            ]
+        );
+    }
+}
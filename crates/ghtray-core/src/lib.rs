@@ -1,5 +1,11 @@
+pub mod auth;
+pub mod backend;
 pub mod config;
+pub mod demo;
 pub mod github;
 pub mod logging;
 pub mod models;
+pub mod oauth;
 pub mod state;
+#[cfg(test)]
+mod stub_server;
@@ -1,29 +1,356 @@
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use std::collections::HashMap;
+
+use crate::config::{DEFAULT_HOST, SavedSearch, TEAM_SECTION_ID};
 
 // ── GraphQL response types ──────────────────────────────────────────────────
 
 #[derive(Debug, Deserialize)]
 pub struct GqlResponse {
     pub data: GqlData,
+    /// Errors for the parts of `data` GitHub couldn't resolve
+    #[serde(default)]
+    pub errors: Vec<GqlError>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Searches that failed outright come back as `null`; treat them as empty.
+#[derive(Debug, Default, Deserialize)]
 pub struct GqlData {
-    #[serde(rename = "needsReview")]
+    #[serde(rename = "needsReview", default, deserialize_with = "null_as_default")]
     pub needs_review: SearchResult,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub authored: SearchResult,
-    #[serde(rename = "reviewedByMe")]
+    #[serde(rename = "reviewedByMe", default, deserialize_with = "null_as_default")]
     pub reviewed_by_me: SearchResult,
-    #[serde(rename = "recentlyMerged")]
+    #[serde(
+        rename = "recentlyMerged",
+        default,
+        deserialize_with = "null_as_default"
+    )]
     pub recently_merged: SearchResult,
+    #[serde(
+        rename = "assignedIssues",
+        default,
+        deserialize_with = "null_as_default"
+    )]
+    pub assigned_issues: SearchResult<Issue>,
+    #[serde(
+        rename = "mentionedIssues",
+        default,
+        deserialize_with = "null_as_default"
+    )]
+    pub mentioned_issues: SearchResult<Issue>,
+    #[serde(rename = "rateLimit", default)]
+    pub rate_limit: Option<RateLimit>,
+    /// Saved and team searches by alias (see `saved_search_alias` and
+    /// `team_search_alias`); `None` if one failed
+    #[serde(flatten)]
+    pub saved: HashMap<String, Option<SearchResult>>,
+}
+
+/// GraphQL alias of the `index`th saved search.
+pub fn saved_search_alias(index: usize) -> String {
+    format!("saved{index}")
+}
+
+/// GraphQL alias of the `index`th team view search.
+pub fn team_search_alias(index: usize) -> String {
+    format!("team{index}")
+}
+
+impl GqlData {
+    /// Team view search results, in alias order.
+    pub fn team_searches(&self) -> impl Iterator<Item = &SearchResult> {
+        (0..)
+            .map_while(|i| self.saved.get(&team_search_alias(i)))
+            .flatten()
+    }
+
+    /// Look up a search result by its GraphQL alias.
+    pub fn search_mut(&mut self, alias: &str) -> Option<&mut SearchResult> {
+        match alias {
+            "needsReview" => Some(&mut self.needs_review),
+            "authored" => Some(&mut self.authored),
+            "reviewedByMe" => Some(&mut self.reviewed_by_me),
+            "recentlyMerged" => Some(&mut self.recently_merged),
+            _ => self.saved.get_mut(alias).and_then(Option::as_mut),
+        }
+    }
+
+    /// Look up an issue search result by its GraphQL alias.
+    pub fn issue_search_mut(&mut self, alias: &str) -> Option<&mut SearchResult<Issue>> {
+        match alias {
+            "assignedIssues" => Some(&mut self.assigned_issues),
+            "mentionedIssues" => Some(&mut self.mentioned_issues),
+            _ => None,
+        }
+    }
+
+    /// Searches whose results were cut short by the per-search cap.
+    pub fn truncated_searches(&self, saved: &[SavedSearch]) -> Vec<Truncation> {
+        let saved = saved.iter().enumerate().filter_map(|(i, search)| {
+            let result = self.saved.get(&saved_search_alias(i))?.as_ref()?;
+            Some(Truncation::of(&search.name, result))
+        });
+        let team = self
+            .team_searches()
+            .map(|result| Truncation::of("Team PRs", result));
+        [
+            Truncation::of("Review requests", &self.needs_review),
+            Truncation::of("Your PRs", &self.authored),
+            Truncation::of("Reviewed by you", &self.reviewed_by_me),
+            Truncation::of("Recently merged", &self.recently_merged),
+            Truncation::of("Assigned issues", &self.assigned_issues),
+            Truncation::of("Mentioned issues", &self.mentioned_issues),
+        ]
+        .into_iter()
+        .chain(saved)
+        .chain(team)
+        .filter(|t| (t.shown as u32) < t.total)
+        .collect()
+    }
 }
 
 #[derive(Debug, Deserialize)]
-pub struct SearchResult {
+#[serde(bound(deserialize = "T: serde::de::DeserializeOwned"))]
+pub struct SearchResult<T = PullRequest> {
     #[serde(rename = "issueCount")]
-    pub _issue_count: u32,
-    pub nodes: Vec<PullRequest>,
+    pub issue_count: u32,
+    #[serde(rename = "pageInfo")]
+    pub page_info: Option<PageInfo>,
+    #[serde(deserialize_with = "skip_unmatched")]
+    pub nodes: Vec<T>,
+}
+
+impl<T> Default for SearchResult<T> {
+    fn default() -> Self {
+        Self {
+            issue_count: 0,
+            page_info: None,
+            nodes: Vec::new(),
+        }
+    }
+}
+
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Nodes GitHub refused to resolve (e.g. in a SAML-protected org) are `null`.
+fn skip_nulls<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let nodes = Vec::<Option<T>>::deserialize(deserializer)?;
+    Ok(nodes.into_iter().flatten().collect())
+}
+
+/// Search nodes that are `null`, or `{}` because they're not of the type
+/// the `... on PullRequest` fragment asks for (an issue in a saved search),
+/// are dropped instead of failing the whole response.
+pub(crate) fn skip_unmatched<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    let nodes = Vec::<Option<serde_json::Value>>::deserialize(deserializer)?;
+    nodes
+        .into_iter()
+        .flatten()
+        .filter(|node| node.get("id").is_some())
+        .map(|node| serde_json::from_value(node).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+/// One entry of a GraphQL `errors` array.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GqlError {
+    pub message: String,
+    #[serde(rename = "type")]
+    pub error_type: Option<String>,
+    #[serde(default)]
+    pub extensions: Option<serde_json::Value>,
+}
+
+/// A GraphQL error we know how to explain to the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    /// The org enforces SAML SSO and the token isn't authorized for it
+    SamlRequired {
+        org: Option<String>,
+    },
+    /// The token can't see a resource (missing scope, OAuth app restrictions)
+    Forbidden {
+        message: String,
+    },
+    /// The GraphQL point budget is used up
+    RateLimited,
+    Other {
+        message: String,
+    },
+}
+
+impl From<&GqlError> for ApiError {
+    fn from(err: &GqlError) -> Self {
+        let saml_failure = err
+            .extensions
+            .as_ref()
+            .and_then(|ext| ext.get("saml_failure"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if saml_failure || err.message.contains("SAML") {
+            return ApiError::SamlRequired {
+                org: quoted_org(&err.message),
+            };
+        }
+        match err.error_type.as_deref() {
+            Some("FORBIDDEN") => ApiError::Forbidden {
+                message: err.message.clone(),
+            },
+            Some("RATE_LIMITED") => ApiError::RateLimited,
+            _ => ApiError::Other {
+                message: err.message.clone(),
+            },
+        }
+    }
+}
+
+/// GitHub names the org in backticks when it names it at all
+/// ("the `acme` organization has enabled SAML ...").
+fn quoted_org(message: &str) -> Option<String> {
+    let start = message.find('`')? + 1;
+    let len = message[start..].find('`')?;
+    let org = &message[start..start + len];
+    (!org.is_empty() && !org.contains(' ')).then(|| org.to_string())
+}
+
+impl ApiError {
+    /// Short menu warning ("Authorize SSO for org acme").
+    pub fn warning(&self) -> String {
+        match self {
+            ApiError::SamlRequired { org: Some(org) } => format!("Authorize SSO for org {org}"),
+            ApiError::SamlRequired { org: None } => {
+                "Authorize SSO to see PRs from a SAML-protected org".to_string()
+            }
+            ApiError::Forbidden { message } => match quoted_org(message) {
+                Some(org) => format!("Access to org {org} is restricted"),
+                None => "Some PRs are hidden: access forbidden".to_string(),
+            },
+            ApiError::RateLimited => "GitHub API rate limit exceeded".to_string(),
+            ApiError::Other { message } => message.clone(),
+        }
+    }
+
+    /// Page where the user can fix the problem, if there is one.
+    pub fn help_url(&self, host: &str) -> Option<String> {
+        match self {
+            ApiError::SamlRequired { org: Some(org) } => {
+                Some(format!("https://{host}/orgs/{org}/sso"))
+            }
+            ApiError::SamlRequired { org: None } => Some(
+                "https://docs.github.com/en/authentication/authenticating-with-saml-single-sign-on"
+                    .to_string(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::Forbidden { message } | ApiError::Other { message } => f.write_str(message),
+            _ => f.write_str(&self.warning()),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl GqlResponse {
+    /// Typed, de-duplicated errors that came back alongside the data.
+    pub fn api_errors(&self) -> Vec<ApiError> {
+        let mut errors: Vec<ApiError> = Vec::new();
+        for err in &self.errors {
+            let err = ApiError::from(err);
+            if !errors.contains(&err) {
+                errors.push(err);
+            }
+        }
+        errors
+    }
+}
+
+impl<T> SearchResult<T> {
+    pub fn is_truncated(&self) -> bool {
+        (self.nodes.len() as u32) < self.issue_count
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PageInfo {
+    #[serde(rename = "hasNextPage")]
+    pub has_next_page: bool,
+    #[serde(rename = "endCursor")]
+    pub end_cursor: Option<String>,
+}
+
+/// GraphQL API budget as of the last query.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Points the query cost (summed over all pages of one fetch)
+    pub cost: u32,
+    /// Points left in the current window
+    pub remaining: u32,
+    #[serde(rename = "resetAt")]
+    pub reset_at: DateTime<Utc>,
+}
+
+impl RateLimit {
+    /// How long to wait before the next poll. Below `threshold` remaining
+    /// points the interval stretches so the budget lasts until `reset_at`;
+    /// with nothing left, polling pauses until the reset.
+    pub fn poll_delay(
+        &self,
+        base: std::time::Duration,
+        threshold: u32,
+        now: DateTime<Utc>,
+    ) -> std::time::Duration {
+        let until_reset = (self.reset_at - now).to_std().unwrap_or_default();
+        if self.remaining == 0 {
+            return base.max(until_reset);
+        }
+        if self.remaining >= threshold {
+            return base;
+        }
+        let polls_left = (self.remaining / self.cost.max(1)).max(1);
+        base.max(until_reset / polls_left)
+    }
+}
+
+/// A search that returned fewer PRs than GitHub reports as matching.
+#[derive(Debug, Clone)]
+pub struct Truncation {
+    pub label: String,
+    pub shown: usize,
+    pub total: u32,
+}
+
+impl Truncation {
+    fn of<T>(label: &str, result: &SearchResult<T>) -> Self {
+        Self {
+            label: label.to_string(),
+            shown: result.nodes.len(),
+            total: result.issue_count,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -46,13 +373,181 @@ pub struct PullRequest {
     pub review_decision: Option<String>,
     #[serde(rename = "latestReviews")]
     pub latest_reviews: Option<ReviewConnection>,
+    #[serde(rename = "reviewRequests")]
+    pub review_requests: Option<ReviewRequestConnection>,
+    #[serde(rename = "timelineItems")]
+    pub timeline_items: Option<TimelineConnection>,
     pub commits: Option<CommitConnection>,
+    pub mergeable: Option<String>,
+    #[serde(rename = "mergeStateStatus")]
+    pub merge_state_status: Option<String>,
+    pub additions: Option<u32>,
+    pub deletions: Option<u32>,
+    #[serde(rename = "changedFiles")]
+    pub changed_files: Option<u32>,
+    pub labels: Option<LabelConnection>,
+    #[serde(rename = "baseRefName")]
+    pub base_ref_name: Option<String>,
+    #[serde(rename = "headRefName")]
+    pub head_ref_name: Option<String>,
+    /// Opened from a fork, so the head branch lives in another repo
+    #[serde(rename = "isCrossRepository")]
+    pub is_cross_repository: Option<bool>,
+    #[serde(rename = "mergeQueueEntry")]
+    pub merge_queue_entry: Option<MergeQueueEntry>,
+    /// Present when auto-merge is enabled
+    #[serde(rename = "autoMergeRequest")]
+    pub auto_merge_request: Option<AutoMergeRequest>,
+    #[serde(rename = "reviewThreads")]
+    pub review_threads: Option<ReviewThreadConnection>,
+}
+
+/// Review threads, first 100; `total_count` counts them all.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReviewThreadConnection {
+    #[serde(rename = "totalCount")]
+    pub total_count: u32,
+    #[serde(deserialize_with = "skip_nulls")]
+    pub nodes: Vec<ReviewThread>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReviewThread {
+    #[serde(rename = "isResolved")]
+    pub is_resolved: bool,
+}
+
+impl ReviewThreadConnection {
+    pub fn unresolved(&self) -> u32 {
+        self.nodes.iter().filter(|t| !t.is_resolved).count() as u32
+    }
+}
+
+/// A PR's place in its base branch's merge queue.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeQueueEntry {
+    /// 1-based; the head of the queue is 1
+    pub position: Option<u32>,
+    /// QUEUED, AWAITING_CHECKS, MERGEABLE, UNMERGEABLE or LOCKED
+    pub state: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct AutoMergeRequest {
+    #[serde(rename = "enabledAt")]
+    pub enabled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct LabelConnection {
+    #[serde(deserialize_with = "skip_nulls")]
+    pub nodes: Vec<Label>,
+}
+
+/// A PR label; `color` is the hex code without `#`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Label {
+    pub name: String,
+    #[serde(default)]
+    pub color: String,
+}
+
+/// An issue from the assigned / mentioned searches. Categorized into the
+/// same `CategorizedPr` rows as pull requests.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Issue {
+    pub id: String,
+    pub number: u32,
+    pub title: String,
+    pub url: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<DateTime<Utc>>,
+    pub repository: Repository,
+    pub author: Option<Actor>,
+    pub labels: Option<LabelConnection>,
+}
+
+/// A thread from the REST notifications inbox (`GET /notifications`).
+#[derive(Debug, Deserialize, Clone)]
+pub struct Notification {
+    /// Thread ID, used to mark it as read
+    pub id: String,
+    /// mention, comment, review_requested, team_mention, ...
+    pub reason: String,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub subject: NotificationSubject,
+    pub repository: NotificationRepository,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct NotificationSubject {
+    pub title: String,
+    /// API URL of the issue, PR, commit or release; missing for discussions
+    pub url: Option<String>,
+    #[serde(rename = "type")]
+    pub subject_type: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct NotificationRepository {
+    pub full_name: String,
+    pub html_url: String,
+}
+
+impl Notification {
+    /// Web page for the subject, falling back to the repository page.
+    pub fn html_url(&self, host: &str) -> String {
+        let Some((_, path)) = self
+            .subject
+            .url
+            .as_deref()
+            .and_then(|url| url.split_once("/repos/"))
+        else {
+            return self.repository.html_url.clone();
+        };
+        let path = path
+            .replacen("/pulls/", "/pull/", 1)
+            .replacen("/commits/", "/commit/", 1);
+        format!("https://{host}/{path}")
+    }
+
+    /// Issue or PR number from the subject URL, 0 for other subjects.
+    pub fn number(&self) -> u32 {
+        match self.subject.subject_type.as_str() {
+            "Issue" | "PullRequest" => self
+                .subject
+                .url
+                .as_deref()
+                .and_then(|url| url.rsplit('/').next()?.parse().ok())
+                .unwrap_or(0),
+            _ => 0,
+        }
+    }
+}
+
+/// Merge state of one PR, from a follow-up `nodes(ids:)` query.
+#[derive(Debug, Deserialize, Clone)]
+pub struct MergeState {
+    pub id: String,
+    pub mergeable: Option<String>,
+    #[serde(rename = "mergeStateStatus")]
+    pub merge_state_status: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Repository {
     #[serde(rename = "nameWithOwner")]
     pub name_with_owner: String,
+    /// Fetched for open PRs only
+    #[serde(rename = "defaultBranchRef", default)]
+    pub default_branch_ref: Option<BranchRef>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BranchRef {
+    pub name: String,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -73,6 +568,42 @@ pub struct Review {
     pub submitted_at: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReviewRequestConnection {
+    #[serde(deserialize_with = "skip_nulls")]
+    pub nodes: Vec<ReviewRequest>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReviewRequest {
+    #[serde(rename = "requestedReviewer")]
+    pub requested_reviewer: Option<RequestedReviewer>,
+}
+
+/// A user (`login`) or a team (`name`, `combinedSlug`) asked to review.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RequestedReviewer {
+    pub login: Option<String>,
+    pub name: Option<String>,
+    #[serde(rename = "combinedSlug")]
+    pub combined_slug: Option<String>,
+}
+
+/// `timelineItems(itemTypes: [REVIEW_REQUESTED_EVENT])`
+#[derive(Debug, Deserialize, Clone)]
+pub struct TimelineConnection {
+    #[serde(deserialize_with = "skip_nulls")]
+    pub nodes: Vec<ReviewRequestedEvent>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReviewRequestedEvent {
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(rename = "requestedReviewer")]
+    pub requested_reviewer: Option<RequestedReviewer>,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct CommitConnection {
     pub nodes: Vec<CommitNode>,
@@ -95,6 +626,108 @@ pub struct CommitInfo {
 #[derive(Debug, Deserialize, Clone)]
 pub struct StatusCheck {
     pub state: String,
+    pub contexts: Option<CheckContextConnection>,
+}
+
+impl StatusCheck {
+    /// Check runs and commit statuses that failed, in rollup order.
+    pub fn failing_checks(&self) -> Vec<FailingCheck> {
+        self.contexts
+            .iter()
+            .flat_map(|contexts| &contexts.nodes)
+            .filter_map(CheckContext::as_failure)
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CheckContextConnection {
+    #[serde(deserialize_with = "skip_nulls")]
+    pub nodes: Vec<CheckContext>,
+}
+
+/// Either a `CheckRun` (name, conclusion, detailsUrl) or a legacy
+/// `StatusContext` (context, state, targetUrl).
+#[derive(Debug, Deserialize, Clone)]
+pub struct CheckContext {
+    pub name: Option<String>,
+    pub conclusion: Option<String>,
+    #[serde(rename = "detailsUrl")]
+    pub details_url: Option<String>,
+    pub context: Option<String>,
+    pub state: Option<String>,
+    #[serde(rename = "targetUrl")]
+    pub target_url: Option<String>,
+}
+
+impl CheckContext {
+    fn as_failure(&self) -> Option<FailingCheck> {
+        let conclusion = self.conclusion.as_deref().or(self.state.as_deref())?;
+        let failed = matches!(
+            conclusion,
+            "FAILURE" | "ERROR" | "TIMED_OUT" | "CANCELLED" | "ACTION_REQUIRED" | "STARTUP_FAILURE"
+        );
+        failed.then(|| FailingCheck {
+            name: self
+                .name
+                .clone()
+                .or_else(|| self.context.clone())
+                .unwrap_or_default(),
+            conclusion: conclusion.to_string(),
+            details_url: self.details_url.clone().or_else(|| self.target_url.clone()),
+        })
+    }
+}
+
+/// A failed check on the PR's head commit, linked to its job page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailingCheck {
+    pub name: String,
+    pub conclusion: String,
+    pub details_url: Option<String>,
+}
+
+// ── Review sub-state ────────────────────────────────────────────────────────
+
+/// Why a PR is waiting on the viewer's review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewSubState {
+    /// No review from the viewer yet, or nothing new since it
+    NeverReviewed,
+    /// The author pushed after the viewer's latest review
+    NewCommits,
+    /// Review was requested again after the viewer's latest review
+    ReRequested,
+}
+
+impl ReviewSubState {
+    /// Short marker for the menu row, if any.
+    pub fn marker(&self) -> Option<&'static str> {
+        match self {
+            ReviewSubState::NeverReviewed => None,
+            ReviewSubState::NewCommits => Some("new commits"),
+            ReviewSubState::ReRequested => Some("re-requested"),
+        }
+    }
+
+    fn notification_title(&self) -> &'static str {
+        match self {
+            ReviewSubState::NeverReviewed => "Review Requested",
+            ReviewSubState::NewCommits => "New Commits Since Your Review",
+            ReviewSubState::ReRequested => "Re-review Requested",
+        }
+    }
+}
+
+// ── Viewer ──────────────────────────────────────────────────────────────────
+
+/// The signed-in user an account fetches as.
+#[derive(Debug, Clone, Default)]
+pub struct Viewer {
+    pub login: String,
+    /// Teams the viewer belongs to, as "org/team-slug"
+    pub teams: Vec<String>,
 }
 
 // ── Bucket model ────────────────────────────────────────────────────────────
@@ -102,60 +735,119 @@ pub struct StatusCheck {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Bucket {
     NeedsYourReview,
+    TeamReviewRequested,
     WaitingForReviewers,
     ReturnedToYou,
+    CiFailing,
     Approved,
     Drafts,
     RecentlyMerged,
     WaitingForAuthor,
+    UpdatedSinceYourReview,
+    ApprovedByYou,
+    CommentedByYou,
+    Stale,
+    /// Result of a saved search; each search is its own section
+    SavedSearch,
+    AssignedIssues,
+    MentionedIssues,
+    /// Unread threads from the notifications inbox
+    Notifications,
 }
 
 impl Bucket {
     pub fn label(&self) -> &'static str {
         match self {
             Bucket::NeedsYourReview => "Needs Your Review",
+            Bucket::TeamReviewRequested => "Team Review Requested",
             Bucket::WaitingForReviewers => "Waiting for Reviewers",
             Bucket::ReturnedToYou => "Returned to You",
+            Bucket::CiFailing => "CI Failing",
             Bucket::Approved => "Approved",
             Bucket::Drafts => "Drafts",
             Bucket::RecentlyMerged => "Recently Merged",
             Bucket::WaitingForAuthor => "Waiting for Author",
+            Bucket::UpdatedSinceYourReview => "Updated Since Your Review",
+            Bucket::ApprovedByYou => "Approved by You",
+            Bucket::CommentedByYou => "Commented by You",
+            Bucket::Stale => "Stale",
+            Bucket::SavedSearch => "Saved Search",
+            Bucket::AssignedIssues => "Assigned Issues",
+            Bucket::MentionedIssues => "Mentioned Issues",
+            Bucket::Notifications => "Notifications",
+        }
+    }
+
+    /// Label in the team view, where the PRs aren't the viewer's own.
+    pub fn team_label(&self) -> &'static str {
+        match self {
+            Bucket::ReturnedToYou => "Changes Requested",
+            _ => self.label(),
         }
     }
 
     pub fn id(&self) -> &'static str {
         match self {
             Bucket::NeedsYourReview => "needs_your_review",
+            Bucket::TeamReviewRequested => "team_review_requested",
             Bucket::WaitingForReviewers => "waiting_for_reviewers",
             Bucket::ReturnedToYou => "returned_to_you",
+            Bucket::CiFailing => "ci_failing",
             Bucket::Approved => "approved",
             Bucket::Drafts => "drafts",
             Bucket::RecentlyMerged => "recently_merged",
             Bucket::WaitingForAuthor => "waiting_for_author",
+            Bucket::UpdatedSinceYourReview => "updated_since_your_review",
+            Bucket::ApprovedByYou => "approved_by_you",
+            Bucket::CommentedByYou => "commented_by_you",
+            Bucket::Stale => "stale",
+            Bucket::SavedSearch => "saved_search",
+            Bucket::AssignedIssues => "assigned_issues",
+            Bucket::MentionedIssues => "mentioned_issues",
+            Bucket::Notifications => "notifications",
         }
     }
 
     pub fn display_order() -> &'static [Bucket] {
         &[
             Bucket::NeedsYourReview,
+            Bucket::TeamReviewRequested,
             Bucket::WaitingForReviewers,
             Bucket::ReturnedToYou,
+            Bucket::CiFailing,
             Bucket::Approved,
             Bucket::Drafts,
             Bucket::RecentlyMerged,
+            Bucket::UpdatedSinceYourReview,
             Bucket::WaitingForAuthor,
+            Bucket::ApprovedByYou,
+            Bucket::CommentedByYou,
+            Bucket::AssignedIssues,
+            Bucket::MentionedIssues,
+            Bucket::Notifications,
+            Bucket::Stale,
         ]
     }
 
     pub fn from_id(id: &str) -> Option<Bucket> {
         match id {
             "needs_your_review" => Some(Bucket::NeedsYourReview),
+            "team_review_requested" => Some(Bucket::TeamReviewRequested),
             "waiting_for_reviewers" => Some(Bucket::WaitingForReviewers),
             "returned_to_you" => Some(Bucket::ReturnedToYou),
+            "ci_failing" => Some(Bucket::CiFailing),
             "approved" => Some(Bucket::Approved),
             "drafts" => Some(Bucket::Drafts),
             "recently_merged" => Some(Bucket::RecentlyMerged),
             "waiting_for_author" => Some(Bucket::WaitingForAuthor),
+            "updated_since_your_review" => Some(Bucket::UpdatedSinceYourReview),
+            "approved_by_you" => Some(Bucket::ApprovedByYou),
+            "commented_by_you" => Some(Bucket::CommentedByYou),
+            "stale" => Some(Bucket::Stale),
+            "saved_search" => Some(Bucket::SavedSearch),
+            "assigned_issues" => Some(Bucket::AssignedIssues),
+            "mentioned_issues" => Some(Bucket::MentionedIssues),
+            "notifications" => Some(Bucket::Notifications),
             _ => None,
         }
     }
@@ -163,9 +855,16 @@ impl Bucket {
 
 // ── Categorized PR (for display and caching) ────────────────────────────────
 
+/// A menu row: a pull request, or an issue in the issue buckets.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CategorizedPr {
     pub id: String,
+    /// GitHub host the PR came from
+    #[serde(default = "default_host")]
+    pub host: String,
+    /// Tag of the account the PR was fetched for (empty for the default account)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub account: String,
     pub number: u32,
     pub title: String,
     pub url: String,
@@ -177,6 +876,214 @@ pub struct CategorizedPr {
     pub last_commit_sha: Option<String>,
     pub last_commit_date: Option<DateTime<Utc>>,
     pub ci_status: Option<String>,
+    /// Team the review was requested from, for `TeamReviewRequested`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+    /// Why the viewer's review is wanted, for review-request buckets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_state: Option<ReviewSubState>,
+    /// Failed checks on the head commit, for `CiFailing`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failing_checks: Vec<FailingCheck>,
+    /// MERGEABLE, CONFLICTING or UNKNOWN, for authored PRs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mergeable: Option<String>,
+    /// BEHIND, BLOCKED, CLEAN, DIRTY, ... for authored PRs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_state_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub additions: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deletions: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changed_files: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<Label>,
+    /// Section ID of the saved search that found the PR, for `SavedSearch`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    /// Why GitHub notified the viewer ("mention", "comment"), for `Notifications`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Branch the PR merges into
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_ref: Option<String>,
+    /// Branch the PR merges from; only set for same-repo PRs from a branch
+    /// other than the default one, since only those can be stacked on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head_ref: Option<String>,
+    /// Number of the open PR in the same repo this one is stacked on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stacked_on: Option<u32>,
+    /// Merge queue entry, for authored PRs that are queued
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_queue: Option<MergeQueueEntry>,
+    /// Auto-merge is enabled, for authored PRs
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub auto_merge: bool,
+    /// Unresolved review threads, for authored PRs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unresolved_threads: Option<u32>,
+    /// All review threads, for authored PRs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_threads: Option<u32>,
+}
+
+fn default_host() -> String {
+    DEFAULT_HOST.to_string()
+}
+
+/// An empty row on the default host; categorizers fill in what they know
+/// and leave the rest to this.
+impl Default for CategorizedPr {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            host: default_host(),
+            account: String::new(),
+            number: 0,
+            title: String::new(),
+            url: String::new(),
+            repo: String::new(),
+            author: String::new(),
+            bucket: Bucket::WaitingForReviewers,
+            created_at: None,
+            updated_at: None,
+            last_commit_sha: None,
+            last_commit_date: None,
+            ci_status: None,
+            team: None,
+            review_state: None,
+            failing_checks: Vec::new(),
+            mergeable: None,
+            merge_state_status: None,
+            additions: None,
+            deletions: None,
+            changed_files: None,
+            labels: Vec::new(),
+            search: None,
+            reason: None,
+            base_ref: None,
+            head_ref: None,
+            stacked_on: None,
+            merge_queue: None,
+            auto_merge: false,
+            unresolved_threads: None,
+            total_threads: None,
+        }
+    }
+}
+
+impl CategorizedPr {
+    /// Unique key across hosts and accounts. Node IDs are only unique per
+    /// host, so non-default hosts are prefixed; github.com keeps the bare ID.
+    /// Tagged accounts are prefixed too, since two accounts can see the same PR.
+    pub fn key(&self) -> String {
+        let id = if self.host == DEFAULT_HOST {
+            self.id.clone()
+        } else {
+            format!("{}/{}", self.host, self.id)
+        };
+        let key = if self.account.is_empty() {
+            id
+        } else {
+            format!("{}:{id}", self.account)
+        };
+        // A saved search can list a PR that is also in a built-in bucket
+        match &self.search {
+            Some(search) => format!("{key}#{search}"),
+            None => key,
+        }
+    }
+
+    /// ID of the menu section the PR is listed in.
+    pub fn section_id(&self) -> &str {
+        self.search.as_deref().unwrap_or(self.bucket.id())
+    }
+
+    /// Whether the row belongs to the team view rather than the viewer's own sections.
+    pub fn is_team(&self) -> bool {
+        self.search.as_deref() == Some(TEAM_SECTION_ID)
+    }
+
+    /// Lines added plus lines deleted, if GitHub reported them.
+    pub fn total_lines(&self) -> Option<u32> {
+        Some(self.additions? + self.deletions?)
+    }
+
+    /// XS/S/M/L/XL by lines changed.
+    pub fn size_label(&self) -> Option<&'static str> {
+        let label = match self.total_lines()? {
+            0..10 => "XS",
+            10..30 => "S",
+            30..100 => "M",
+            100..500 => "L",
+            _ => "XL",
+        };
+        Some(label)
+    }
+
+    pub fn is_conflicting(&self) -> bool {
+        self.mergeable.as_deref() == Some("CONFLICTING")
+            || self.merge_state_status.as_deref() == Some("DIRTY")
+    }
+
+    /// GitHub computes mergeability lazily, so the first answer is often UNKNOWN.
+    pub fn merge_state_unknown(&self) -> bool {
+        self.mergeable.as_deref() == Some("UNKNOWN")
+            || self.merge_state_status.as_deref() == Some("UNKNOWN")
+    }
+
+    /// "#123 " for issues and PRs, nothing for notifications about other things.
+    pub fn number_prefix(&self) -> String {
+        if self.number == 0 {
+            String::new()
+        } else {
+            format!("#{} ", self.number)
+        }
+    }
+
+    /// "2/5 unresolved" for PRs with review threads.
+    pub fn thread_marker(&self) -> Option<String> {
+        let total = self.total_threads.filter(|total| *total > 0)?;
+        Some(format!("{}/{total} unresolved", self.unresolved_threads?))
+    }
+
+    /// "queued #3" for PRs in the merge queue, "auto-merge on" for PRs that
+    /// merge themselves once they can.
+    pub fn queue_marker(&self) -> Option<String> {
+        match &self.merge_queue {
+            Some(MergeQueueEntry {
+                position: Some(position),
+                ..
+            }) => Some(format!("queued #{position}")),
+            Some(_) => Some("queued".to_string()),
+            None if self.auto_merge => Some("auto-merge on".to_string()),
+            None => None,
+        }
+    }
+
+    /// Short marker for the menu row: conflicts, behind the base branch, or
+    /// blocked by branch protection.
+    pub fn merge_marker(&self) -> Option<&'static str> {
+        if self.is_conflicting() {
+            return Some("⚠ conflicts");
+        }
+        match self.merge_state_status.as_deref() {
+            Some("BEHIND") => Some("behind base"),
+            Some("BLOCKED") => Some("blocked"),
+            _ => None,
+        }
+    }
+}
+
+/// Format a line count compactly (e.g., "840", "1.2k", "15k")
+pub fn compact_count(n: u32) -> String {
+    match n {
+        0..1000 => n.to_string(),
+        1000..10_000 => format!("{:.1}k", n as f64 / 1000.0),
+        _ => format!("{}k", n / 1000),
+    }
 }
 
 /// Format a datetime as a compact relative time string (e.g., "2m", "4h", "3d", "2mo", "1y")
@@ -209,9 +1116,33 @@ pub fn relative_time(dt: DateTime<Utc>) -> String {
 
 #[derive(Debug, Clone, Serialize)]
 pub enum Transition {
-    New { pr: CategorizedPr },
-    Moved { pr: CategorizedPr, from: Bucket },
-    Removed { pr: CategorizedPr },
+    New {
+        pr: CategorizedPr,
+    },
+    Moved {
+        pr: CategorizedPr,
+        from: Bucket,
+    },
+    /// Same review-request bucket, but the reason changed (e.g. new commits)
+    ReviewStateChanged {
+        pr: CategorizedPr,
+        from: Option<ReviewSubState>,
+    },
+    /// An authored PR that used to merge cleanly now has conflicts
+    Conflicting {
+        pr: CategorizedPr,
+    },
+    /// An open PR left the merge queue without merging
+    RemovedFromQueue {
+        pr: CategorizedPr,
+    },
+    /// An authored PR with no unresolved review threads now has some
+    UnresolvedThreads {
+        pr: CategorizedPr,
+    },
+    Removed {
+        pr: CategorizedPr,
+    },
 }
 
 impl Transition {
@@ -219,44 +1150,305 @@ impl Transition {
     pub fn notification_text(&self) -> Option<(&str, String)> {
         match self {
             Transition::New { pr } => match pr.bucket {
-                Bucket::NeedsYourReview => Some((
-                    "Review Requested",
-                    format!("#{} {} ({})", pr.number, pr.title, short_repo(&pr.repo)),
-                )),
-                Bucket::ReturnedToYou => Some((
-                    "Changes Requested",
-                    format!("#{} {} ({})", pr.number, pr.title, short_repo(&pr.repo)),
-                )),
-                Bucket::Approved => Some((
-                    "PR Approved",
-                    format!("#{} {} ({})", pr.number, pr.title, short_repo(&pr.repo)),
-                )),
+                Bucket::NeedsYourReview => Some((review_title(pr), notification_body(pr))),
+                Bucket::TeamReviewRequested => Some(("Team Review Requested", team_body(pr))),
+                Bucket::ReturnedToYou => Some(("Changes Requested", notification_body(pr))),
+                Bucket::CiFailing => Some(("CI Failing", failing_body(pr))),
+                Bucket::Approved => Some(("PR Approved", notification_body(pr))),
+                Bucket::UpdatedSinceYourReview => {
+                    Some(("Updated Since Your Review", notification_body(pr)))
+                }
+                Bucket::AssignedIssues => Some(("Issue Assigned", notification_body(pr))),
+                Bucket::MentionedIssues => Some(("Mentioned in Issue", notification_body(pr))),
+                // Only what the searches can't see; review requests already notify
+                Bucket::Notifications => match pr.reason.as_deref() {
+                    Some("mention") => Some(("Mentioned", notification_body(pr))),
+                    Some("team_mention") => Some(("Team Mentioned", notification_body(pr))),
+                    Some("comment") => Some(("New Comment", notification_body(pr))),
+                    _ => None,
+                },
                 _ => None,
             },
             Transition::Moved { pr, from } => match (&from, &pr.bucket) {
-                (_, Bucket::NeedsYourReview) => Some((
-                    "Review Requested",
-                    format!("#{} {} ({})", pr.number, pr.title, short_repo(&pr.repo)),
-                )),
-                (_, Bucket::ReturnedToYou) => Some((
-                    "Changes Requested",
-                    format!("#{} {} ({})", pr.number, pr.title, short_repo(&pr.repo)),
-                )),
-                (_, Bucket::Approved) => Some((
-                    "PR Approved",
-                    format!("#{} {} ({})", pr.number, pr.title, short_repo(&pr.repo)),
-                )),
-                (_, Bucket::RecentlyMerged) => Some((
-                    "PR Merged",
-                    format!("#{} {} ({})", pr.number, pr.title, short_repo(&pr.repo)),
-                )),
+                (_, Bucket::NeedsYourReview) => Some((review_title(pr), notification_body(pr))),
+                // Losing a direct request while the team one remains isn't news
+                (Bucket::NeedsYourReview, Bucket::TeamReviewRequested) => None,
+                (_, Bucket::TeamReviewRequested) => Some(("Team Review Requested", team_body(pr))),
+                (_, Bucket::ReturnedToYou) => Some(("Changes Requested", notification_body(pr))),
+                (_, Bucket::CiFailing) => Some(("CI Failing", failing_body(pr))),
+                (_, Bucket::Approved) => Some(("PR Approved", notification_body(pr))),
+                (_, Bucket::RecentlyMerged) => Some(("PR Merged", notification_body(pr))),
+                (_, Bucket::UpdatedSinceYourReview) => {
+                    Some(("Updated Since Your Review", notification_body(pr)))
+                }
+                (_, Bucket::AssignedIssues) => Some(("Issue Assigned", notification_body(pr))),
+                (_, Bucket::ApprovedByYou) => Some(("Approved by You", notification_body(pr))),
+                (_, Bucket::CommentedByYou) => Some(("Commented by You", notification_body(pr))),
                 _ => None,
             },
+            // No previous state: saved before sub-states existed
+            Transition::ReviewStateChanged { from: None, .. } => None,
+            Transition::ReviewStateChanged { pr, .. } => match pr.review_state {
+                Some(ReviewSubState::NewCommits | ReviewSubState::ReRequested)
+                    if pr.bucket == Bucket::NeedsYourReview =>
+                {
+                    Some((review_title(pr), notification_body(pr)))
+                }
+                _ => None,
+            },
+            Transition::Conflicting { pr } => Some(("Merge Conflict", notification_body(pr))),
+            Transition::RemovedFromQueue { pr } => {
+                Some(("Removed from Merge Queue", notification_body(pr)))
+            }
+            Transition::UnresolvedThreads { pr } => {
+                Some(("Unresolved Review Threads", threads_body(pr)))
+            }
             Transition::Removed { .. } => None,
         }
     }
 }
 
+fn review_title(pr: &CategorizedPr) -> &'static str {
+    pr.review_state
+        .unwrap_or(ReviewSubState::NeverReviewed)
+        .notification_title()
+}
+
+/// "team: #123 Title (repo)" for team review requests.
+fn team_body(pr: &CategorizedPr) -> String {
+    match &pr.team {
+        Some(team) => format!("{team}: {}", notification_body(pr)),
+        None => notification_body(pr),
+    }
+}
+
+/// "#123 Title (repo): check, check" for CI failures.
+fn failing_body(pr: &CategorizedPr) -> String {
+    let names: Vec<&str> = pr.failing_checks.iter().map(|c| c.name.as_str()).collect();
+    if names.is_empty() {
+        notification_body(pr)
+    } else {
+        format!("{}: {}", notification_body(pr), names.join(", "))
+    }
+}
+
+/// "#123 Title (repo): 2 unresolved threads" for new review threads.
+fn threads_body(pr: &CategorizedPr) -> String {
+    match pr.unresolved_threads {
+        Some(1) => format!("{}: 1 unresolved thread", notification_body(pr)),
+        Some(n) => format!("{}: {n} unresolved threads", notification_body(pr)),
+        None => notification_body(pr),
+    }
+}
+
+/// "#123 Title (repo)", plus the account tag when there is one.
+fn notification_body(pr: &CategorizedPr) -> String {
+    let body = format!(
+        "{}{} ({})",
+        pr.number_prefix(),
+        pr.title,
+        short_repo(&pr.repo)
+    );
+    if pr.account.is_empty() {
+        body
+    } else {
+        format!("{body} [{}]", pr.account)
+    }
+}
+
 fn short_repo(repo: &str) -> &str {
     repo.split('/').next_back().unwrap_or(repo)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+
+    /// A search result holding `shown` PRs out of `total` matches.
+    fn search(shown: u32, total: u32) -> serde_json::Value {
+        let nodes: Vec<_> = (0..shown)
+            .map(|i| {
+                json!({
+                    "id": format!("PR_{i}"), "number": i, "title": "Fix", "url": "https://github.com/acme/app/pull/1",
+                    "repository": { "nameWithOwner": "acme/app" },
+                })
+            })
+            .collect();
+        json!({ "issueCount": total, "pageInfo": null, "nodes": nodes })
+    }
+
+    #[test]
+    fn truncated_searches_lists_capped_searches() {
+        let data: GqlData = serde_json::from_value(json!({
+            "needsReview": search(2, 2),
+            "authored": search(3, 250),
+            "reviewedByMe": search(0, 0),
+            "recentlyMerged": search(1, 1),
+        }))
+        .unwrap();
+        let truncated = data.truncated_searches(&[]);
+        assert_eq!(truncated.len(), 1);
+        assert_eq!(truncated[0].label, "Your PRs");
+        assert_eq!((truncated[0].shown, truncated[0].total), (3, 250));
+    }
+
+    #[test]
+    fn search_nodes_skip_nulls_and_unmatched_types() {
+        let result: SearchResult = serde_json::from_value(json!({
+            "issueCount": 3,
+            "pageInfo": null,
+            "nodes": [null, {}, {
+                "id": "PR_1", "number": 1, "title": "Fix", "url": "https://github.com/acme/app/pull/1",
+                "repository": { "nameWithOwner": "acme/app" },
+            }],
+        }))
+        .unwrap();
+        assert_eq!(result.nodes.len(), 1);
+    }
+
+    fn rate_limit(cost: u32, remaining: u32, reset_in_secs: i64) -> (RateLimit, DateTime<Utc>) {
+        let now = Utc::now();
+        let limit = RateLimit {
+            cost,
+            remaining,
+            reset_at: now + chrono::Duration::seconds(reset_in_secs),
+        };
+        (limit, now)
+    }
+
+    #[test]
+    fn poll_delay_keeps_base_above_threshold() {
+        let (limit, now) = rate_limit(2, 1000, 3600);
+        assert_eq!(
+            limit.poll_delay(Duration::from_secs(60), 500, now),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn poll_delay_stretches_below_threshold() {
+        // 100 points at 10 a poll: 10 polls over the hour left
+        let (limit, now) = rate_limit(10, 100, 3600);
+        assert_eq!(
+            limit.poll_delay(Duration::from_secs(60), 500, now),
+            Duration::from_secs(360)
+        );
+    }
+
+    #[test]
+    fn poll_delay_waits_for_reset_when_exhausted() {
+        let (limit, now) = rate_limit(10, 0, 3600);
+        assert_eq!(
+            limit.poll_delay(Duration::from_secs(60), 500, now),
+            Duration::from_secs(3600)
+        );
+        // A reset in the past doesn't shorten the base interval
+        let (limit, now) = rate_limit(10, 0, -60);
+        assert_eq!(
+            limit.poll_delay(Duration::from_secs(60), 500, now),
+            Duration::from_secs(60)
+        );
+    }
+
+    fn gql_error(error_type: Option<&str>, message: &str) -> GqlError {
+        GqlError {
+            message: message.to_string(),
+            error_type: error_type.map(String::from),
+            extensions: None,
+        }
+    }
+
+    #[test]
+    fn saml_errors_name_the_org() {
+        let err = gql_error(
+            Some("FORBIDDEN"),
+            "Resource protected by organization SAML enforcement. You must grant your OAuth token access to the `acme` organization.",
+        );
+        let api_error = ApiError::from(&err);
+        assert_eq!(
+            api_error,
+            ApiError::SamlRequired {
+                org: Some("acme".to_string())
+            }
+        );
+        assert_eq!(
+            api_error.help_url("github.com").as_deref(),
+            Some("https://github.com/orgs/acme/sso")
+        );
+    }
+
+    #[test]
+    fn maps_forbidden_and_rate_limited_errors() {
+        let forbidden = ApiError::from(&gql_error(
+            Some("FORBIDDEN"),
+            "Although you appear to have the correct authorization credentials, the `acme` organization has enabled OAuth App access restrictions.",
+        ));
+        assert!(matches!(forbidden, ApiError::Forbidden { .. }));
+        assert_eq!(forbidden.warning(), "Access to org acme is restricted");
+        assert_eq!(forbidden.help_url("github.com"), None);
+
+        let rate_limited =
+            ApiError::from(&gql_error(Some("RATE_LIMITED"), "API rate limit exceeded"));
+        assert_eq!(rate_limited, ApiError::RateLimited);
+        assert_eq!(rate_limited.warning(), "GitHub API rate limit exceeded");
+
+        let other = ApiError::from(&gql_error(None, "Something went wrong"));
+        assert_eq!(other.to_string(), "Something went wrong");
+    }
+
+    #[test]
+    fn api_errors_are_deduplicated() {
+        let response = GqlResponse {
+            data: GqlData::default(),
+            errors: vec![
+                gql_error(Some("RATE_LIMITED"), "API rate limit exceeded"),
+                gql_error(Some("RATE_LIMITED"), "API rate limit exceeded"),
+            ],
+        };
+        assert_eq!(response.api_errors(), [ApiError::RateLimited]);
+    }
+
+    #[test]
+    fn keys_prefix_non_default_hosts() {
+        let pr = |host: &str| -> CategorizedPr {
+            serde_json::from_value(json!({
+                "id": "PR_1", "host": host, "number": 1, "title": "Fix", "url": "",
+                "repo": "acme/app", "author": "me", "bucket": "NeedsYourReview",
+            }))
+            .unwrap()
+        };
+        assert_eq!(pr(DEFAULT_HOST).key(), "PR_1");
+        assert_eq!(pr("ghe.acme.com").key(), "ghe.acme.com/PR_1");
+
+        // Two accounts can see the same PR
+        let mut tagged = pr("ghe.acme.com");
+        tagged.account = "work".to_string();
+        assert_eq!(tagged.key(), "work:ghe.acme.com/PR_1");
+        let (_, body) = Transition::New { pr: tagged }.notification_text().unwrap();
+        assert_eq!(body, "#1 Fix (app) [work]");
+    }
+
+    #[test]
+    fn size_labels_by_lines_changed() {
+        let pr = |additions: Option<u32>, deletions: u32| -> CategorizedPr {
+            serde_json::from_value(json!({
+                "id": "PR_1", "number": 1, "title": "Fix", "url": "", "repo": "acme/app",
+                "author": "me", "bucket": "WaitingForReviewers",
+                "additions": additions, "deletions": deletions,
+            }))
+            .unwrap()
+        };
+        assert_eq!(pr(Some(5), 4).size_label(), Some("XS"));
+        assert_eq!(pr(Some(5), 5).size_label(), Some("S"));
+        assert_eq!(pr(Some(99), 0).size_label(), Some("M"));
+        assert_eq!(pr(Some(400), 99).size_label(), Some("L"));
+        assert_eq!(pr(Some(400), 100).size_label(), Some("XL"));
+        assert_eq!(pr(None, 10).size_label(), None);
+
+        assert_eq!(compact_count(840), "840");
+        assert_eq!(compact_count(1250), "1.2k");
+        assert_eq!(compact_count(15_400), "15k");
+    }
+}
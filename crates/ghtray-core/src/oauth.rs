@@ -0,0 +1,252 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::config::{AppConfig, HostConfig};
+
+/// Scopes requested by the device flow: private repo PRs and team membership.
+const SCOPES: &str = "repo read:org";
+
+/// OAuth App client ID baked in at build time, if any.
+const DEFAULT_CLIENT_ID: Option<&str> = option_env!("GHTRAY_OAUTH_CLIENT_ID");
+
+/// Code the user enters at `verification_uri` to approve the sign-in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceCode {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub expires_in: u64,
+    pub interval: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+    interval: Option<u64>,
+}
+
+/// GitHub OAuth device authorization flow for one host. github.com's
+/// endpoints come from the config so the flow can run against a local stub
+/// server; GHES hosts use their own `/login` endpoints and OAuth App.
+pub struct DeviceFlow {
+    client_id: String,
+    device_code_url: String,
+    access_token_url: String,
+    agent: ureq::Agent,
+    /// Waits between polls; replaced in tests
+    sleep: Box<dyn Fn(Duration) + Send + Sync>,
+}
+
+impl DeviceFlow {
+    pub fn from_config(config: &AppConfig, host: &HostConfig) -> Result<Self> {
+        let (client_id, device_code_url, access_token_url) = if host.is_default() {
+            (
+                config.oauth_client_id.as_deref().or(DEFAULT_CLIENT_ID),
+                config.oauth_device_code_url.clone(),
+                config.oauth_access_token_url.clone(),
+            )
+        } else {
+            (
+                host.oauth_client_id.as_deref(),
+                format!("https://{}/login/device/code", host.hostname),
+                format!("https://{}/login/oauth/access_token", host.hostname),
+            )
+        };
+        let Some(client_id) = client_id.filter(|id| !id.is_empty()) else {
+            if host.is_default() {
+                bail!("No OAuth client ID configured. Set `oauth_client_id` in the config file.");
+            }
+            bail!(
+                "No OAuth client ID configured for {}. Set `oauth_client_id` on the host in the config file.",
+                host.hostname
+            );
+        };
+
+        Ok(Self {
+            client_id: client_id.to_string(),
+            device_code_url,
+            access_token_url,
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(30))
+                .user_agent(concat!("ghtray/", env!("CARGO_PKG_VERSION")))
+                .build(),
+            sleep: Box::new(std::thread::sleep),
+        })
+    }
+
+    /// Wait between polls with `sleep` instead of blocking the thread.
+    pub fn with_sleep(mut self, sleep: impl Fn(Duration) + Send + Sync + 'static) -> Self {
+        self.sleep = Box::new(sleep);
+        self
+    }
+
+    /// Step 1: ask GitHub for a device code and user code.
+    pub fn request_code(&self) -> Result<DeviceCode> {
+        self.agent
+            .post(&self.device_code_url)
+            .set("Accept", "application/json")
+            .send_form(&[("client_id", &self.client_id), ("scope", SCOPES)])
+            .context("Failed to request device code")?
+            .into_json()
+            .context("Failed to parse device code response")
+    }
+
+    /// Step 2: poll until the user approves, denies or the code expires.
+    /// `cancelled` is checked before every poll so a newer sign-in can
+    /// abandon this one.
+    pub fn poll_token(&self, code: &DeviceCode, cancelled: impl Fn() -> bool) -> Result<String> {
+        let expires_at: DateTime<Utc> =
+            Utc::now() + chrono::Duration::seconds(code.expires_in as i64);
+        let mut interval = code.interval.max(1);
+
+        loop {
+            (self.sleep)(Duration::from_secs(interval));
+            if cancelled() {
+                bail!("Sign-in cancelled");
+            }
+            if Utc::now() > expires_at {
+                bail!("Device code expired. Start the sign-in again.");
+            }
+
+            let resp: TokenResponse = self
+                .agent
+                .post(&self.access_token_url)
+                .set("Accept", "application/json")
+                .send_form(&[
+                    ("client_id", self.client_id.as_str()),
+                    ("device_code", code.device_code.as_str()),
+                    ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ])
+                .context("Failed to poll for access token")?
+                .into_json()
+                .context("Failed to parse access token response")?;
+
+            if let Some(token) = resp.access_token {
+                return Ok(token);
+            }
+
+            match resp.error.as_deref() {
+                Some("authorization_pending") => {}
+                // GitHub asks us to back off; it sends the new interval
+                Some("slow_down") => interval = resp.interval.unwrap_or(interval + 5),
+                Some("expired_token") => bail!("Device code expired. Start the sign-in again."),
+                Some("access_denied") => bail!("Sign-in was denied."),
+                Some(other) => bail!(
+                    "Sign-in failed: {}",
+                    resp.error_description.as_deref().unwrap_or(other)
+                ),
+                None => bail!("Unexpected access token response"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DEFAULT_HOST;
+    use crate::stub_server;
+    use std::sync::{Arc, Mutex};
+
+    /// A flow against `base_url` that records its sleeps instead of sleeping.
+    fn flow(base_url: &str) -> (DeviceFlow, Arc<Mutex<Vec<u64>>>) {
+        let config = AppConfig {
+            oauth_client_id: Some("client".to_string()),
+            oauth_device_code_url: format!("{base_url}/login/device/code"),
+            oauth_access_token_url: format!("{base_url}/login/oauth/access_token"),
+            ..AppConfig::default()
+        };
+        let sleeps = Arc::new(Mutex::new(Vec::new()));
+        let recorded = sleeps.clone();
+        let flow = DeviceFlow::from_config(&config, &HostConfig::new(DEFAULT_HOST))
+            .unwrap()
+            .with_sleep(move |d| recorded.lock().unwrap().push(d.as_secs()));
+        (flow, sleeps)
+    }
+
+    #[test]
+    fn enterprise_hosts_use_their_own_endpoints_and_app() {
+        let config = AppConfig {
+            oauth_client_id: Some("dotcom".to_string()),
+            ..AppConfig::default()
+        };
+        let host = HostConfig {
+            oauth_client_id: Some("ghes".to_string()),
+            ..HostConfig::new("ghe.acme.com")
+        };
+        let flow = DeviceFlow::from_config(&config, &host).unwrap();
+        assert_eq!(flow.client_id, "ghes");
+        assert_eq!(
+            flow.device_code_url,
+            "https://ghe.acme.com/login/device/code"
+        );
+        assert_eq!(
+            flow.access_token_url,
+            "https://ghe.acme.com/login/oauth/access_token"
+        );
+
+        // The github.com app isn't registered on the GHES host
+        assert!(DeviceFlow::from_config(&config, &HostConfig::new("ghe.acme.com")).is_err());
+    }
+
+    fn code() -> DeviceCode {
+        DeviceCode {
+            device_code: "dev123".to_string(),
+            user_code: "ABCD-1234".to_string(),
+            verification_uri: "https://github.com/login/device".to_string(),
+            expires_in: 900,
+            interval: 5,
+        }
+    }
+
+    #[test]
+    fn polls_through_pending_and_slow_down_to_token() {
+        let (base_url, server) = stub_server::serve(vec![
+            (200, r#"{"error":"authorization_pending"}"#),
+            (200, r#"{"error":"slow_down","interval":10}"#),
+            (200, r#"{"access_token":"gho_token","token_type":"bearer"}"#),
+        ]);
+        let (flow, sleeps) = flow(&base_url);
+
+        assert_eq!(flow.poll_token(&code(), || false).unwrap(), "gho_token");
+        assert_eq!(*sleeps.lock().unwrap(), vec![5, 5, 10]);
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /login/oauth/access_token "));
+        assert!(requests[0].contains("device_code=dev123"));
+    }
+
+    #[test]
+    fn reports_denied_sign_in() {
+        let (base_url, server) = stub_server::serve(vec![(200, r#"{"error":"access_denied"}"#)]);
+        let (flow, _) = flow(&base_url);
+
+        let err = flow.poll_token(&code(), || false).unwrap_err();
+        assert_eq!(err.to_string(), "Sign-in was denied.");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn reports_expired_code() {
+        let (base_url, server) = stub_server::serve(vec![(200, r#"{"error":"expired_token"}"#)]);
+        let (flow, _) = flow(&base_url);
+
+        let err = flow.poll_token(&code(), || false).unwrap_err();
+        assert!(err.to_string().starts_with("Device code expired"));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn stops_when_cancelled_without_polling() {
+        let (base_url, server) = stub_server::serve(Vec::new());
+        let (flow, _) = flow(&base_url);
+
+        let err = flow.poll_token(&code(), || true).unwrap_err();
+        assert_eq!(err.to_string(), "Sign-in cancelled");
+        assert!(server.join().unwrap().is_empty());
+    }
+}
@@ -5,8 +5,10 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::config::DEFAULT_HOST;
 use crate::models::CategorizedPr;
 
+/// Last fetched PRs for one account, keyed by `CategorizedPr::key()`.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AppState {
     pub last_fetch: Option<DateTime<Utc>>,
@@ -27,12 +29,27 @@ pub fn data_dir() -> PathBuf {
     dir
 }
 
-pub fn state_file_path() -> PathBuf {
-    data_dir().join("ghtray-state.json")
+/// State file for an account (see `backend::Account::key`). The default
+/// github.com account keeps the original file name.
+pub fn state_file_path(account_key: &str) -> PathBuf {
+    if account_key == DEFAULT_HOST {
+        return data_dir().join("ghtray-state.json");
+    }
+    let name: String = account_key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "@.-_".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    data_dir().join(format!("ghtray-state-{name}.json"))
 }
 
-pub fn load_state() -> AppState {
-    let path = state_file_path();
+pub fn load_state(account_key: &str) -> AppState {
+    let path = state_file_path(account_key);
     if path.exists() {
         fs::read_to_string(&path)
             .ok()
@@ -43,8 +60,8 @@ pub fn load_state() -> AppState {
     }
 }
 
-pub fn save_state(state: &AppState) -> Result<()> {
-    let path = state_file_path();
+pub fn save_state(account_key: &str, state: &AppState) -> Result<()> {
+    let path = state_file_path(account_key);
     let json = serde_json::to_string_pretty(state)?;
     fs::write(&path, json)?;
     Ok(())
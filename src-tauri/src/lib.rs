@@ -1,23 +1,27 @@
-use ghtray_core::config::AppConfig;
+use ghtray_core::auth;
+use ghtray_core::backend::{self, Account};
+use ghtray_core::config::{
+    AccountConfig, AppConfig, AuthSource, BotPrHandling, DEFAULT_HOST, HostConfig, LabelRule,
+    SavedSearch, Section, StalePrHandling, TeamGrouping, colliding_saved_searches,
+};
+use ghtray_core::demo::DemoBackend;
 use ghtray_core::github::{self, GhStatus};
 use ghtray_core::logging;
-use ghtray_core::models::{self, Bucket, CategorizedPr, Transition};
+use ghtray_core::models::{
+    self, ApiError, Bucket, CategorizedPr, RateLimit, Transition, Truncation, Viewer,
+};
+use ghtray_core::oauth::DeviceFlow;
 use ghtray_core::state;
 use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
-use std::sync::atomic::{AtomicBool, Ordering};
-
-static DEMO_MODE: AtomicBool = AtomicBool::new(false);
-
-fn is_demo() -> bool {
-    DEMO_MODE.load(Ordering::Relaxed)
-}
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tauri::{
     AppHandle, Manager, RunEvent, WebviewUrl, WebviewWindowBuilder, WindowEvent,
     image::Image,
     menu::{
-        IconMenuItem, IconMenuItemBuilder, MenuBuilder, MenuItem, MenuItemBuilder,
-        PredefinedMenuItem,
+        IconMenuItem, IconMenuItemBuilder, IsMenuItem, MenuBuilder, MenuItem, MenuItemBuilder,
+        PredefinedMenuItem, Submenu, SubmenuBuilder,
     },
 };
 use tauri_plugin_autostart::ManagerExt;
@@ -26,27 +30,149 @@ use tauri_plugin_notification::NotificationExt;
 // ── App state ───────────────────────────────────────────────────────────────
 
 struct GhTrayState {
-    viewer: Mutex<Option<String>>,
+    /// One backend per configured account
+    accounts: Mutex<Vec<Account>>,
+    /// Started with offline backends (`--demo`, `--replay`); auth settings don't replace them
+    offline: bool,
+    /// `--record <dir>`: save every GraphQL response, its query and the config here
+    record_dir: Option<PathBuf>,
+    /// Last saved PR state per account key, used to diff the next fetch against
+    snapshots: Mutex<HashMap<String, state::AppState>>,
+    /// Viewer login and teams per account key
+    viewers: Mutex<HashMap<String, Viewer>>,
     prs: Mutex<Vec<CategorizedPr>>,
     all_prs: Mutex<Vec<CategorizedPr>>,
+    /// "showing N of M" notes for searches cut short by pagination
+    truncated: Mutex<Vec<String>>,
+    /// Partial-failure warnings from the last fetch ("Authorize SSO for org acme")
+    warnings: Mutex<Vec<MenuWarning>>,
+    /// Latest GraphQL API budget per account label
+    rate_limits: Mutex<HashMap<String, RateLimit>>,
+    /// Notifications inbox per account key, refreshed with conditional requests
+    notifications: Mutex<HashMap<String, github::NotificationInbox>>,
+    /// Logins the team view covers, resolved from the team slug once
+    team_logins: Mutex<Option<Vec<String>>>,
     config: Mutex<AppConfig>,
     last_error: Mutex<Option<String>>,
+    device_login: Mutex<DeviceLogin>,
+}
+
+/// A menu warning, clickable when there's a page that fixes it.
+#[derive(Debug, Clone)]
+struct MenuWarning {
+    text: String,
+    url: Option<String>,
+}
+
+/// Progress of the OAuth device-flow sign-in started from settings.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+enum DeviceLogin {
+    Idle,
+    Pending {
+        user_code: String,
+        verification_uri: String,
+    },
+    Done,
+    Failed {
+        message: String,
+    },
 }
 
 impl GhTrayState {
-    fn new() -> Self {
+    /// `replay_config` is the config a `--replay` capture was recorded with.
+    fn new(
+        offline_accounts: Option<Vec<Account>>,
+        replay_config: Option<AppConfig>,
+        record_dir: Option<PathBuf>,
+    ) -> Self {
+        let config = replay_config.unwrap_or_else(AppConfig::load);
+        let offline = offline_accounts.is_some();
+        let accounts = offline_accounts.unwrap_or_else(|| backend::from_config(&config));
+        let accounts = with_recording(accounts, record_dir.as_deref(), &config);
         Self {
-            viewer: Mutex::new(None),
+            accounts: Mutex::new(accounts),
+            offline,
+            record_dir,
+            snapshots: Mutex::new(HashMap::new()),
+            viewers: Mutex::new(HashMap::new()),
             prs: Mutex::new(Vec::new()),
             all_prs: Mutex::new(Vec::new()),
-            config: Mutex::new(AppConfig::load()),
+            truncated: Mutex::new(Vec::new()),
+            warnings: Mutex::new(Vec::new()),
+            rate_limits: Mutex::new(HashMap::new()),
+            notifications: Mutex::new(HashMap::new()),
+            team_logins: Mutex::new(None),
+            config: Mutex::new(config),
             last_error: Mutex::new(None),
+            device_login: Mutex::new(DeviceLogin::Idle),
+        }
+    }
+}
+
+impl GhTrayState {
+    fn accounts(&self) -> Vec<Account> {
+        self.accounts.lock().unwrap().clone()
+    }
+
+    /// Swap in the live backends for the current auth, host and account settings.
+    fn reload_accounts(&self, config: &AppConfig) {
+        if self.offline {
+            return;
+        }
+        let accounts = backend::from_config(config);
+        *self.accounts.lock().unwrap() =
+            with_recording(accounts, self.record_dir.as_deref(), config);
+        self.viewers.lock().unwrap().clear();
+        self.rate_limits.lock().unwrap().clear();
+        self.notifications.lock().unwrap().clear();
+        *self.team_logins.lock().unwrap() = None;
+    }
+
+    /// Save `config`, and the capture's copy when recording. Offline sessions
+    /// may run with a capture's config, so they never overwrite the user's.
+    fn save_config(&self, config: &AppConfig) -> Result<(), String> {
+        if !self.offline {
+            config.save().map_err(|e| e.to_string())?;
+        }
+        if let Some(dir) = &self.record_dir
+            && let Err(e) = backend::save_capture_config(dir, config)
+        {
+            logging::log_error(&format!("{e:#}"));
+        }
+        Ok(())
+    }
+}
+
+/// Wrap the accounts' backends to record responses when `--record` was given.
+fn with_recording(
+    accounts: Vec<Account>,
+    record_dir: Option<&Path>,
+    config: &AppConfig,
+) -> Vec<Account> {
+    let Some(dir) = record_dir else {
+        return accounts;
+    };
+    match backend::record(accounts.clone(), dir, config) {
+        Ok(recording) => recording,
+        Err(e) => {
+            logging::log_error(&format!("{e:#}"));
+            accounts
         }
     }
 }
 
 // ── Helpers ─────────────────────────────────────────────────────────────────
 
+/// How an account is named in errors and notes: its tag, or its host.
+fn account_label(account: &Account) -> String {
+    if account.tag.is_empty() {
+        account.backend.host().to_string()
+    } else {
+        account.tag.clone()
+    }
+}
+
 fn truncate(s: &str, max: usize) -> String {
     if s.chars().count() > max {
         let t: String = s.chars().take(max.saturating_sub(3)).collect();
@@ -87,10 +213,39 @@ struct BucketEntry {
     label: String,
     visible: bool,
     badge: bool,
+    bots: BotPrHandling,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AccountEntry {
+    tag: String,
+    host: String,
+    login: Option<String>,
+    badge: bool,
+    notifications: bool,
+}
+
+/// An account the token fields sign in or store a token for, keyed by its
+/// OS keyring entry (`AccountConfig::keyring_user`).
+#[derive(Debug, Clone, Serialize)]
+struct AuthAccountEntry {
+    key: String,
+    label: String,
+    token_source: Option<String>,
+    keyring_token: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct RateLimitEntry {
+    account: String,
+    remaining: u32,
+    cost: u32,
+    reset_at: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(Debug, Clone, Serialize)]
 struct GhStatusInfo {
+    source: String,
     ok: bool,
     message: String,
 }
@@ -99,9 +254,27 @@ struct GhStatusInfo {
 struct SettingsData {
     poll_interval_secs: u64,
     merged_window_days: i64,
+    max_results_per_search: u32,
+    fetch_issues: bool,
+    fetch_notifications: bool,
+    team_slug: String,
+    team_members: Vec<String>,
+    team_group_by: TeamGrouping,
     notifications_enabled: bool,
     notification_sound: bool,
     autostart: bool,
+    auth_source: AuthSource,
+    api_base_url: String,
+    hosts: Vec<String>,
+    accounts: Vec<AccountEntry>,
+    bot_logins: Vec<String>,
+    stale_after_days: u32,
+    stale_prs: StalePrHandling,
+    repo_stale_after_days: HashMap<String, u32>,
+    label_rules: Vec<LabelRule>,
+    saved_searches: Vec<SavedSearch>,
+    rate_limits: Vec<RateLimitEntry>,
+    auth_accounts: Vec<AuthAccountEntry>,
     buckets: Vec<BucketEntry>,
     orgs: Vec<OrgEntry>,
     gh_status: GhStatusInfo,
@@ -141,83 +314,177 @@ fn get_settings(app: AppHandle, state: tauri::State<'_, GhTrayState>) -> Setting
         .collect();
 
     let buckets: Vec<BucketEntry> = config
-        .ordered_buckets()
+        .ordered_sections()
         .iter()
-        .map(|b| BucketEntry {
-            id: b.id().to_string(),
-            label: b.label().to_string(),
-            visible: config.is_bucket_visible(b.id()),
-            badge: config.counts_for_badge(b.id()),
+        .map(|section| {
+            let id = section.id();
+            BucketEntry {
+                label: section.label().to_string(),
+                visible: config.is_bucket_visible(&id),
+                badge: config.counts_for_badge(&id),
+                bots: config.bot_handling(&id),
+                id,
+            }
         })
         .collect();
 
     let autostart = app.autolaunch().is_enabled().unwrap_or(false);
 
-    let gh_status = if is_demo() {
-        GhStatusInfo {
-            ok: true,
-            message: "Demo mode".to_string(),
+    let gh_status = status_info(&state.accounts());
+    let hosts = config
+        .effective_hosts()
+        .into_iter()
+        .map(|host| host.hostname)
+        .collect();
+    let accounts = config
+        .accounts
+        .iter()
+        .map(|a| AccountEntry {
+            tag: a.tag.clone(),
+            host: a.host.clone(),
+            login: a.login.clone(),
+            badge: a.badge,
+            notifications: a.notifications,
+        })
+        .collect();
+    let mut rate_limits: Vec<RateLimitEntry> = state
+        .rate_limits
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(account, rate)| RateLimitEntry {
+            account: account.clone(),
+            remaining: rate.remaining,
+            cost: rate.cost,
+            reset_at: rate.reset_at,
+        })
+        .collect();
+    rate_limits.sort_by(|a, b| a.account.cmp(&b.account));
+    // Accounts without a login on the same host share a keyring entry
+    let mut auth_accounts: Vec<AuthAccountEntry> = Vec::new();
+    for account in config.effective_accounts() {
+        let key = account.keyring_user();
+        if auth_accounts.iter().any(|a| a.key == key) {
+            continue;
         }
-    } else {
-        match github::check_gh_status() {
-            GhStatus::Ok => GhStatusInfo {
-                ok: true,
-                message: "Connected".to_string(),
-            },
-            GhStatus::NotInstalled => GhStatusInfo {
-                ok: false,
-                message: "gh CLI not installed. Install from https://cli.github.com".to_string(),
-            },
-            GhStatus::NotAuthenticated(msg) => GhStatusInfo {
-                ok: false,
-                message: format!("Not authenticated. Run `gh auth login`. {msg}"),
+        auth_accounts.push(AuthAccountEntry {
+            label: if account.tag.is_empty() {
+                key.clone()
+            } else {
+                format!("{} ({key})", account.tag)
             },
-        }
-    };
+            token_source: auth::resolve_token(&config, &account)
+                .map(|(_, source)| source.label().to_string()),
+            keyring_token: auth::keyring_token(&key).is_some(),
+            key,
+        });
+    }
 
     SettingsData {
         poll_interval_secs: config.poll_interval_secs,
         merged_window_days: config.merged_window_days,
+        max_results_per_search: config.max_results_per_search,
+        fetch_issues: config.fetch_issues,
+        fetch_notifications: config.fetch_notifications,
+        team_slug: config.team_slug.clone().unwrap_or_default(),
+        team_members: config.team_members.clone(),
+        team_group_by: config.team_group_by,
         notifications_enabled: config.notifications_enabled,
         notification_sound: config.notification_sound,
         autostart,
+        auth_source: config.auth_source,
+        api_base_url: config.api_base_url.clone(),
+        hosts,
+        accounts,
+        bot_logins: config.bot_logins.clone(),
+        stale_after_days: config.stale_after_days.unwrap_or(0),
+        stale_prs: config.stale_prs,
+        repo_stale_after_days: config.repo_stale_after_days.clone(),
+        label_rules: config.label_rules.clone(),
+        saved_searches: config.saved_searches.clone(),
+        rate_limits,
+        auth_accounts,
         buckets,
         orgs,
         gh_status,
     }
 }
 
+/// Status of the first account that isn't usable, or of the first account
+/// if all are fine.
+fn status_info(accounts: &[Account]) -> GhStatusInfo {
+    let mut infos = accounts.iter().map(account_status_info);
+    let first = infos.next().unwrap_or(GhStatusInfo {
+        source: "None".to_string(),
+        ok: false,
+        message: "No GitHub accounts configured".to_string(),
+    });
+    if !first.ok {
+        return first;
+    }
+    infos.find(|info| !info.ok).unwrap_or(first)
+}
+
+fn account_status_info(account: &Account) -> GhStatusInfo {
+    let backend = account.backend.as_ref();
+    let (ok, message) = match backend.status() {
+        GhStatus::Ok if backend.is_live() => (true, "Connected".to_string()),
+        GhStatus::Ok => (true, format!("{} mode", backend.name())),
+        GhStatus::NotInstalled => (
+            false,
+            "gh CLI not installed. Install from https://cli.github.com".to_string(),
+        ),
+        GhStatus::NotAuthenticated(msg) => (
+            false,
+            format!("Not authenticated. {} {msg}", backend.login_hint()),
+        ),
+        GhStatus::Unreachable(msg) => (false, format!("GitHub unreachable. {msg}")),
+    };
+    let source = if account.tag.is_empty() && backend.host() == DEFAULT_HOST {
+        backend.name().to_string()
+    } else {
+        format!("{} ({})", backend.name(), account_label(account))
+    };
+    GhStatusInfo {
+        source,
+        ok,
+        message,
+    }
+}
+
 #[tauri::command]
 fn check_gh(state: tauri::State<'_, GhTrayState>) -> GhStatusInfo {
-    if is_demo() {
-        return GhStatusInfo {
-            ok: true,
-            message: "Demo mode".to_string(),
-        };
-    }
-    match github::check_gh_status() {
-        GhStatus::Ok => {
-            *state.last_error.lock().unwrap() = None;
-            GhStatusInfo {
-                ok: true,
-                message: "Connected".to_string(),
-            }
-        }
-        GhStatus::NotInstalled => GhStatusInfo {
-            ok: false,
-            message: "gh CLI not installed. Install from https://cli.github.com".to_string(),
-        },
-        GhStatus::NotAuthenticated(msg) => GhStatusInfo {
-            ok: false,
-            message: format!("Not authenticated. Run `gh auth login`. {msg}"),
-        },
+    let info = status_info(&state.accounts());
+    if info.ok {
+        *state.last_error.lock().unwrap() = None;
     }
+    info
+}
+
+/// Check a saved search's syntax and price it against the first account.
+#[tauri::command]
+fn check_saved_search(
+    state: tauri::State<'_, GhTrayState>,
+    search: SavedSearch,
+) -> Result<github::SearchCheck, String> {
+    let accounts = state.accounts();
+    let account = accounts.first().ok_or("No account configured")?;
+    let max_per_search = state.config.lock().unwrap().max_results_per_search;
+    github::check_saved_search(account.backend.as_ref(), &search, max_per_search)
+        .map_err(|e| e.to_string())
 }
 
 #[derive(Debug, Clone, Deserialize)]
 struct SaveSettingsPayload {
     poll_interval_secs: u64,
     merged_window_days: i64,
+    max_results_per_search: u32,
+    fetch_issues: bool,
+    fetch_notifications: bool,
+    /// Empty turns the team slug off
+    team_slug: String,
+    team_members: Vec<String>,
+    team_group_by: TeamGrouping,
     blocked_repos: Vec<String>,
     notifications_enabled: bool,
     notification_sound: bool,
@@ -225,6 +492,18 @@ struct SaveSettingsPayload {
     badge_buckets: Vec<String>,
     bucket_order: Vec<String>,
     autostart: bool,
+    auth_source: AuthSource,
+    api_base_url: String,
+    hosts: Vec<String>,
+    accounts: Vec<AccountEntry>,
+    bot_logins: Vec<String>,
+    bot_prs: HashMap<String, BotPrHandling>,
+    /// 0 turns staleness off
+    stale_after_days: u32,
+    stale_prs: StalePrHandling,
+    repo_stale_after_days: HashMap<String, u32>,
+    label_rules: Vec<LabelRule>,
+    saved_searches: Vec<SavedSearch>,
 }
 
 #[tauri::command]
@@ -233,16 +512,68 @@ fn save_settings(
     state: tauri::State<'_, GhTrayState>,
     payload: SaveSettingsPayload,
 ) -> Result<(), String> {
+    let saved_searches: Vec<SavedSearch> = payload
+        .saved_searches
+        .into_iter()
+        .filter(|search| !search.name.trim().is_empty() && !search.query.trim().is_empty())
+        .collect();
+    // Saved searches are keyed by a slug of their name; reject before changing anything
+    if let Some((earlier, later)) = colliding_saved_searches(&saved_searches) {
+        return Err(format!(
+            "Saved searches \"{}\" and \"{}\" would share a section. Rename one of them.",
+            earlier.name, later.name
+        ));
+    }
+
     let mut config = state.config.lock().unwrap();
     config.poll_interval_secs = payload.poll_interval_secs.max(30);
     config.merged_window_days = payload.merged_window_days.max(1);
+    config.max_results_per_search = payload.max_results_per_search.max(1);
+    config.fetch_issues = payload.fetch_issues;
+    config.fetch_notifications = payload.fetch_notifications;
+    let team_slug = Some(payload.team_slug.trim().to_string()).filter(|slug| !slug.is_empty());
+    if config.team_slug != team_slug || config.team_members != payload.team_members {
+        *state.team_logins.lock().unwrap() = None;
+    }
+    config.team_slug = team_slug;
+    config.team_members = payload.team_members;
+    config.team_group_by = payload.team_group_by;
     config.blocked_repos = payload.blocked_repos.into_iter().collect();
     config.notifications_enabled = payload.notifications_enabled;
     config.notification_sound = payload.notification_sound;
     config.hidden_buckets = payload.hidden_buckets.into_iter().collect();
     config.badge_buckets = payload.badge_buckets.into_iter().collect();
     config.bucket_order = payload.bucket_order;
-    config.save().map_err(|e| e.to_string())?;
+    config.bot_logins = payload.bot_logins;
+    config.bot_prs = payload
+        .bot_prs
+        .into_iter()
+        .filter(|(_, handling)| *handling != BotPrHandling::Show)
+        .collect();
+    config.stale_after_days = Some(payload.stale_after_days).filter(|days| *days > 0);
+    config.stale_prs = payload.stale_prs;
+    config.repo_stale_after_days = payload.repo_stale_after_days;
+    config.label_rules = payload
+        .label_rules
+        .into_iter()
+        .filter(|rule| !rule.label.trim().is_empty())
+        .collect();
+    config.saved_searches = saved_searches;
+    let hosts = merge_hosts(&config.hosts, &payload.hosts);
+    let accounts = merge_accounts(&config.accounts, payload.accounts);
+    let auth_changed = config.auth_source != payload.auth_source
+        || config.api_base_url != payload.api_base_url
+        || config.hosts != hosts
+        || config.accounts != accounts;
+    config.auth_source = payload.auth_source;
+    config.api_base_url = payload.api_base_url;
+    config.hosts = hosts;
+    config.accounts = accounts;
+    state.save_config(&config)?;
+
+    if auth_changed {
+        state.reload_accounts(&config);
+    }
 
     // Update autostart
     let mgr = app.autolaunch();
@@ -264,6 +595,180 @@ fn save_settings(
     Ok(())
 }
 
+/// Rebuild the host list from the hostnames entered in settings, keeping the
+/// extra per-host fields of hosts that were already configured. A lone
+/// github.com is stored as an empty list (the default).
+fn merge_hosts(existing: &[HostConfig], hostnames: &[String]) -> Vec<HostConfig> {
+    let mut hosts: Vec<HostConfig> = Vec::new();
+    for name in hostnames {
+        let name = name.trim().trim_end_matches('/').to_lowercase();
+        if name.is_empty() || hosts.iter().any(|h| h.hostname == name) {
+            continue;
+        }
+        let host = existing
+            .iter()
+            .find(|h| h.hostname == name)
+            .cloned()
+            .unwrap_or_else(|| HostConfig::new(&name));
+        hosts.push(host);
+    }
+    if hosts.len() == 1 && hosts[0] == HostConfig::new(DEFAULT_HOST) {
+        hosts.clear();
+    }
+    hosts
+}
+
+/// Apply the accounts edited in settings, keeping tokens of accounts that
+/// already existed (they're never sent to the settings window). Accounts
+/// without a tag are dropped; duplicate tags keep the first.
+fn merge_accounts(existing: &[AccountConfig], entries: Vec<AccountEntry>) -> Vec<AccountConfig> {
+    let mut accounts: Vec<AccountConfig> = Vec::new();
+    for entry in entries {
+        let tag = entry.tag.trim().to_string();
+        if tag.is_empty() || accounts.iter().any(|a| a.tag == tag) {
+            continue;
+        }
+        let host = entry.host.trim().to_lowercase();
+        let mut account =
+            AccountConfig::for_host(if host.is_empty() { DEFAULT_HOST } else { &host });
+        account.tag = tag;
+        account.login = entry
+            .login
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty());
+        account.badge = entry.badge;
+        account.notifications = entry.notifications;
+        account.token = existing
+            .iter()
+            .find(|a| a.tag == account.tag)
+            .and_then(|a| a.token.clone());
+        accounts.push(account);
+    }
+    accounts
+}
+
+/// The account whose keyring entry is `key`, or the first account.
+fn auth_account(config: &AppConfig, key: Option<&str>) -> Result<AccountConfig, String> {
+    let mut accounts = config.effective_accounts().into_iter();
+    match key {
+        Some(key) => accounts
+            .find(|account| account.keyring_user() == key)
+            .ok_or_else(|| format!("No configured account signs in as {key}")),
+        None => accounts
+            .next()
+            .ok_or_else(|| "No GitHub accounts configured".to_string()),
+    }
+}
+
+#[tauri::command]
+fn save_token(
+    state: tauri::State<'_, GhTrayState>,
+    token: String,
+    account: Option<String>,
+) -> Result<(), String> {
+    let token = token.trim();
+    if token.is_empty() {
+        return Err("Token is empty".to_string());
+    }
+    let account = auth_account(&state.config.lock().unwrap(), account.as_deref())?;
+    auth::store_keyring_token(&account.keyring_user(), token).map_err(|e| format!("{e:#}"))?;
+    let config = state.config.lock().unwrap().clone();
+    state.reload_accounts(&config);
+    Ok(())
+}
+
+#[tauri::command]
+fn start_device_login(
+    app: AppHandle,
+    state: tauri::State<'_, GhTrayState>,
+    account: Option<String>,
+) -> Result<DeviceLogin, String> {
+    let config = state.config.lock().unwrap().clone();
+    let account = auth_account(&config, account.as_deref())?;
+    let keyring_user = account.keyring_user();
+    let flow = DeviceFlow::from_config(&config, &config.host_config(&account.host))
+        .map_err(|e| format!("{e:#}"))?;
+    let code = flow.request_code().map_err(|e| format!("{e:#}"))?;
+
+    let pending = DeviceLogin::Pending {
+        user_code: code.user_code.clone(),
+        verification_uri: code.verification_uri.clone(),
+    };
+    *state.device_login.lock().unwrap() = pending.clone();
+    let _ = tauri_plugin_opener::open_url(&code.verification_uri, None::<&str>);
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let state = app.state::<GhTrayState>();
+        // A newer sign-in (or sign-out) replaces the pending code and abandons this poll
+        let is_current = |login: &DeviceLogin| {
+            matches!(
+                login,
+                DeviceLogin::Pending { user_code, .. } if *user_code == code.user_code
+            )
+        };
+
+        let result = flow
+            .poll_token(&code, || !is_current(&state.device_login.lock().unwrap()))
+            .and_then(|token| {
+                // Store under the lock so a sign-out can't land between the
+                // check and the write
+                let login = state.device_login.lock().unwrap();
+                if !is_current(&login) {
+                    return Ok(false);
+                }
+                auth::store_keyring_token(&keyring_user, &token)?;
+                Ok(true)
+            });
+        if !is_current(&state.device_login.lock().unwrap()) {
+            return;
+        }
+
+        match result {
+            Ok(false) => {}
+            Ok(true) => {
+                let config = {
+                    let mut config = state.config.lock().unwrap();
+                    config.auth_source = AuthSource::Token;
+                    if let Err(e) = state.save_config(&config) {
+                        logging::log_error(&e);
+                    }
+                    config.clone()
+                };
+                state.reload_accounts(&config);
+                *state.device_login.lock().unwrap() = DeviceLogin::Done;
+                do_fetch(&app);
+            }
+            Err(e) => {
+                let message = format!("{e:#}");
+                logging::log_error(&message);
+                *state.device_login.lock().unwrap() = DeviceLogin::Failed { message };
+            }
+        }
+    });
+
+    Ok(pending)
+}
+
+#[tauri::command]
+fn device_login_status(state: tauri::State<'_, GhTrayState>) -> DeviceLogin {
+    state.device_login.lock().unwrap().clone()
+}
+
+#[tauri::command]
+fn sign_out(state: tauri::State<'_, GhTrayState>, account: Option<String>) -> Result<(), String> {
+    let account = auth_account(&state.config.lock().unwrap(), account.as_deref())?;
+    {
+        // Abandon any pending sign-in first; it stores its token under this lock
+        let mut login = state.device_login.lock().unwrap();
+        *login = DeviceLogin::Idle;
+        auth::delete_keyring_token(&account.keyring_user()).map_err(|e| format!("{e:#}"))?;
+    }
+    let config = state.config.lock().unwrap().clone();
+    state.reload_accounts(&config);
+    Ok(())
+}
+
 // ── Native tray menu ────────────────────────────────────────────────────────
 
 fn rebuild_tray_menu(
@@ -275,6 +780,18 @@ fn rebuild_tray_menu(
         Text(MenuItem<tauri::Wry>),
         Icon(IconMenuItem<tauri::Wry>),
         Sep(PredefinedMenuItem<tauri::Wry>),
+        Sub(Submenu<tauri::Wry>),
+    }
+
+    impl AnyItem {
+        fn as_item(&self) -> &dyn IsMenuItem<tauri::Wry> {
+            match self {
+                AnyItem::Text(i) => i,
+                AnyItem::Icon(i) => i,
+                AnyItem::Sep(i) => i,
+                AnyItem::Sub(i) => i,
+            }
+        }
     }
 
     let mut items: Vec<AnyItem> = Vec::new();
@@ -290,15 +807,68 @@ fn rebuild_tray_menu(
         items.push(AnyItem::Sep(PredefinedMenuItem::separator(app)?));
     }
 
+    // Warn about data GitHub left out (SAML, forbidden repos)
+    let warnings = app_state.warnings.lock().unwrap().clone();
+    if !warnings.is_empty() {
+        for (i, warning) in warnings.iter().enumerate() {
+            items.push(AnyItem::Text(
+                MenuItemBuilder::with_id(
+                    format!("warning_{i}"),
+                    format!("⚠ {}", truncate(&warning.text, 50)),
+                )
+                .enabled(warning.url.is_some())
+                .build(app)?,
+            ));
+        }
+        items.push(AnyItem::Sep(PredefinedMenuItem::separator(app)?));
+    }
+
+    // Note searches that hit the pagination cap
+    let truncated = app_state.truncated.lock().unwrap().clone();
+    if !truncated.is_empty() {
+        for (i, note) in truncated.iter().enumerate() {
+            items.push(AnyItem::Text(
+                MenuItemBuilder::with_id(format!("truncated_{i}"), format!("ⓘ {note}"))
+                    .enabled(false)
+                    .build(app)?,
+            ));
+        }
+        items.push(AnyItem::Sep(PredefinedMenuItem::separator(app)?));
+    }
+
+    // Note accounts that ran out of API budget
+    let exhausted: Vec<(String, RateLimit)> = app_state
+        .rate_limits
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, rate)| rate.remaining == 0 && rate.reset_at > chrono::Utc::now())
+        .map(|(account, rate)| (account.clone(), *rate))
+        .collect();
+    if !exhausted.is_empty() {
+        for (i, (account, rate)) in exhausted.iter().enumerate() {
+            let reset = rate.reset_at.with_timezone(&chrono::Local).format("%H:%M");
+            items.push(AnyItem::Text(
+                MenuItemBuilder::with_id(
+                    format!("rate_limited_{i}"),
+                    format!("ⓘ {account}: rate limited until {reset}"),
+                )
+                .enabled(false)
+                .build(app)?,
+            ));
+        }
+        items.push(AnyItem::Sep(PredefinedMenuItem::separator(app)?));
+    }
+
     let mut has_buckets = false;
 
-    for bucket in config.ordered_buckets() {
-        let bucket = &bucket;
-        if !config.is_bucket_visible(bucket.id()) {
+    for section in config.ordered_sections() {
+        let id = section.id();
+        if !config.is_bucket_visible(&id) {
             continue;
         }
         let mut bucket_prs: Vec<&CategorizedPr> =
-            prs.iter().filter(|pr| pr.bucket == *bucket).collect();
+            prs.iter().filter(|pr| pr.section_id() == id).collect();
         if bucket_prs.is_empty() {
             continue;
         }
@@ -315,16 +885,34 @@ fn rebuild_tray_menu(
         }
         has_buckets = true;
 
-        items.push(AnyItem::Text(
-            MenuItemBuilder::with_id(
-                format!("bucket_{}", bucket.id()),
-                format!("{} ({})", bucket.label(), bucket_prs.len()),
-            )
-            .enabled(false)
-            .build(app)?,
-        ));
+        let mut header = format!("{} ({})", section.label(), bucket_prs.len());
+        let sizes: Vec<u32> = bucket_prs
+            .iter()
+            .filter_map(|pr| pr.total_lines())
+            .collect();
+        if !sizes.is_empty() {
+            let lines = models::compact_count(sizes.iter().sum());
+            header.push_str(&format!(" · {lines} lines"));
+        }
+        // Stale PRs live in a submenu so they don't crowd the menu
+        let collapsed = section == Section::Bucket(Bucket::Stale);
+        if !collapsed {
+            items.push(AnyItem::Text(
+                MenuItemBuilder::with_id(format!("bucket_{id}"), &header)
+                    .enabled(false)
+                    .build(app)?,
+            ));
+        }
+        let first_row = items.len();
+
+        let total = bucket_prs.len();
+        if config.bot_handling(&id) == BotPrHandling::Collapse {
+            bucket_prs.retain(|pr| !config.is_bot(&pr.author));
+        }
+        let bot_count = total - bucket_prs.len();
 
-        for pr in &bucket_prs {
+        // Stacked PRs follow the PR they build on, indented
+        for (pr, depth) in github::stack_order(&bucket_prs) {
             let repo_short = pr.repo.split('/').next_back().unwrap_or(&pr.repo);
             let ci = ci_indicator(pr.ci_status.as_deref());
             let age = pr.created_at.map(models::relative_time).unwrap_or_default();
@@ -334,35 +922,160 @@ fn rebuild_tray_menu(
                 format!(" · {age}")
             };
 
+            let team_suffix = match &pr.team {
+                Some(team) => format!(" · {team}"),
+                None => String::new(),
+            };
+            let review_marker = match pr.review_state.and_then(|s| s.marker()) {
+                Some(marker) => format!(" · {marker}"),
+                None => String::new(),
+            };
+            let size = match pr.size_label() {
+                Some(size) => format!(" · {size}"),
+                None => String::new(),
+            };
+            let merge_marker = match pr.merge_marker() {
+                Some(marker) => format!(" · {marker}"),
+                None => String::new(),
+            };
+            let account_tag = if pr.account.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", pr.account)
+            };
+
+            // Notification threads are opened or marked as read from a submenu
+            if pr.bucket == Bucket::Notifications {
+                let reason = pr.reason.as_deref().unwrap_or_default().replace('_', " ");
+                let label = format!(
+                    "  {}{} ({}) · {reason}{account_tag}{age_suffix}",
+                    pr.number_prefix(),
+                    truncate(&pr.title, 40),
+                    repo_short,
+                );
+                let open =
+                    MenuItemBuilder::with_id(format!("pr_{}", pr.key()), "Open").build(app)?;
+                let read = MenuItemBuilder::with_id(format!("read_{}", pr.key()), "Mark as Read")
+                    .build(app)?;
+                items.push(AnyItem::Sub(
+                    SubmenuBuilder::with_id(app, format!("notification_{}", pr.key()), label)
+                        .item(&open)
+                        .item(&read)
+                        .build()?,
+                ));
+                continue;
+            }
+
+            let prefix = match config.label_prefix(&pr.labels) {
+                Some(prefix) => format!("{prefix} "),
+                None => String::new(),
+            };
+
+            let indent = match depth {
+                0 => "  ".to_string(),
+                _ => format!("  {}└ ", "   ".repeat(depth - 1)),
+            };
+            let thread_marker = match pr.thread_marker() {
+                Some(marker) => format!(" · {marker}"),
+                None => String::new(),
+            };
+            let queue_marker = match pr.queue_marker() {
+                Some(marker) => format!(" · {marker}"),
+                None => String::new(),
+            };
+            let stack_marker = match pr.stacked_on {
+                Some(parent) => format!(" · blocked by #{parent}"),
+                None => String::new(),
+            };
+
             let label = format!(
-                "  #{} {}{} ({}){}",
+                "{}#{} {}{}{} ({}){}{}{}{}{}{}{}{}{}",
+                indent,
                 pr.number,
+                prefix,
                 truncate(&pr.title, 36),
                 ci,
                 repo_short,
+                size,
+                review_marker,
+                merge_marker,
+                thread_marker,
+                queue_marker,
+                stack_marker,
+                team_suffix,
+                account_tag,
                 age_suffix
             );
 
-            if let Some(avatar_path) = github::avatar_path(&pr.author)
+            if let Some(avatar_path) = github::avatar_path(&pr.host, &pr.author)
                 && let Ok(bytes) = std::fs::read(&avatar_path)
                 && let Ok(icon) = Image::from_bytes(&bytes)
             {
                 items.push(AnyItem::Icon(
                     IconMenuItemBuilder::new(&label)
-                        .id(format!("pr_{}", pr.id))
+                        .id(format!("pr_{}", pr.key()))
                         .icon(icon)
                         .enabled(true)
                         .build(app)?,
                 ));
-                continue;
+            } else {
+                items.push(AnyItem::Text(
+                    MenuItemBuilder::with_id(format!("pr_{}", pr.key()), &label)
+                        .enabled(true)
+                        .build(app)?,
+                ));
             }
 
+            // Link straight to the failing jobs
+            if pr.bucket == Bucket::CiFailing {
+                for (i, check) in pr.failing_checks.iter().take(3).enumerate() {
+                    items.push(AnyItem::Text(
+                        MenuItemBuilder::with_id(
+                            format!("check_{i}_{}", pr.key()),
+                            format!("      ✗ {}", truncate(&check.name, 40)),
+                        )
+                        .enabled(check.details_url.is_some())
+                        .build(app)?,
+                    ));
+                }
+            }
+        }
+
+        if bot_count > 0 {
+            let noun = if bot_count == 1 { "PR" } else { "PRs" };
+            items.push(AnyItem::Text(
+                MenuItemBuilder::with_id(format!("bots_{id}"), format!("  {bot_count} bot {noun}"))
+                    .enabled(false)
+                    .build(app)?,
+            ));
+        }
+
+        if section == Section::Bucket(Bucket::Notifications) {
             items.push(AnyItem::Text(
-                MenuItemBuilder::with_id(format!("pr_{}", pr.id), &label)
+                MenuItemBuilder::with_id("read_all", "  ✓ Mark All as Read")
                     .enabled(true)
                     .build(app)?,
             ));
         }
+
+        if collapsed {
+            let rows = items.split_off(first_row);
+            let mut submenu = SubmenuBuilder::with_id(app, format!("bucket_{id}"), header);
+            for row in &rows {
+                submenu = submenu.item(row.as_item());
+            }
+            items.push(AnyItem::Sub(submenu.build()?));
+        }
+    }
+
+    // Other people's PRs go in a submenu of their own
+    let team_prs: Vec<&CategorizedPr> = prs.iter().filter(|pr| pr.is_team()).collect();
+    if !team_prs.is_empty() {
+        if has_buckets {
+            items.push(AnyItem::Sep(PredefinedMenuItem::separator(app)?));
+        }
+        has_buckets = true;
+        items.push(AnyItem::Sub(team_submenu(app, &team_prs, config)?));
     }
 
     if !has_buckets {
@@ -397,11 +1110,7 @@ fn rebuild_tray_menu(
 
     let mut builder = MenuBuilder::new(app);
     for item in &items {
-        match item {
-            AnyItem::Text(i) => builder = builder.item(i),
-            AnyItem::Icon(i) => builder = builder.item(i),
-            AnyItem::Sep(i) => builder = builder.item(i),
-        }
+        builder = builder.item(item.as_item());
     }
     let menu = builder.build()?;
 
@@ -412,6 +1121,97 @@ fn rebuild_tray_menu(
     Ok(())
 }
 
+/// The team view: PRs grouped by author or by bucket, most recently
+/// updated first within each group.
+fn team_submenu(
+    app: &AppHandle,
+    prs: &[&CategorizedPr],
+    config: &AppConfig,
+) -> tauri::Result<Submenu<tauri::Wry>> {
+    let mut groups: Vec<(String, Vec<&CategorizedPr>)> = Vec::new();
+    match config.team_group_by {
+        TeamGrouping::Author => {
+            let mut authors: Vec<&str> = prs.iter().map(|pr| pr.author.as_str()).collect();
+            authors.sort_by_key(|author| author.to_lowercase());
+            authors.dedup();
+            for author in authors {
+                let rows = prs.iter().filter(|pr| pr.author == author).copied();
+                groups.push((author.to_string(), rows.collect()));
+            }
+        }
+        TeamGrouping::Bucket => {
+            for bucket in Bucket::display_order() {
+                let rows: Vec<&CategorizedPr> = prs
+                    .iter()
+                    .filter(|pr| pr.bucket == *bucket)
+                    .copied()
+                    .collect();
+                if !rows.is_empty() {
+                    groups.push((bucket.team_label().to_string(), rows));
+                }
+            }
+        }
+    }
+
+    let mut submenu = SubmenuBuilder::with_id(app, "team", format!("Team ({})", prs.len()));
+    for (i, (heading, mut rows)) in groups.into_iter().enumerate() {
+        rows.sort_by(|a, b| {
+            b.updated_at
+                .or(b.created_at)
+                .cmp(&a.updated_at.or(a.created_at))
+        });
+        if i > 0 {
+            submenu = submenu.separator();
+        }
+        submenu = submenu.item(
+            &MenuItemBuilder::with_id(
+                format!("team_group_{i}"),
+                format!("{heading} ({})", rows.len()),
+            )
+            .enabled(false)
+            .build(app)?,
+        );
+        for pr in rows {
+            // Each row names whatever the grouping doesn't
+            let detail = match config.team_group_by {
+                TeamGrouping::Author => pr.bucket.team_label(),
+                TeamGrouping::Bucket => &pr.author,
+            };
+            let mut label = format!(
+                "  #{} {}{} ({}) · {detail}",
+                pr.number,
+                truncate(&pr.title, 36),
+                ci_indicator(pr.ci_status.as_deref()),
+                pr.repo.split('/').next_back().unwrap_or(&pr.repo),
+            );
+            if let Some(size) = pr.size_label() {
+                label.push_str(&format!(" · {size}"));
+            }
+            if let Some(marker) = pr.merge_marker() {
+                label.push_str(&format!(" · {marker}"));
+            }
+            if let Some(marker) = pr.thread_marker() {
+                label.push_str(&format!(" · {marker}"));
+            }
+            if let Some(marker) = pr.queue_marker() {
+                label.push_str(&format!(" · {marker}"));
+            }
+            if let Some(parent) = pr.stacked_on {
+                label.push_str(&format!(" · blocked by #{parent}"));
+            }
+            if let Some(at) = pr.created_at {
+                label.push_str(&format!(" · {}", models::relative_time(at)));
+            }
+            submenu = submenu.item(
+                &MenuItemBuilder::with_id(format!("pr_{}", pr.key()), label)
+                    .enabled(true)
+                    .build(app)?,
+            );
+        }
+    }
+    submenu.build()
+}
+
 // ── Notifications ────────────────────────────────────────────────────────────
 
 fn send_notifications(app: &AppHandle, transitions: &[Transition], config: &AppConfig) {
@@ -444,186 +1244,6 @@ fn play_system_sound() {
     });
 }
 
-// ── Demo mode ────────────────────────────────────────────────────────────────
-
-fn demo_prs() -> Vec<CategorizedPr> {
-    use chrono::{Duration, Utc};
-
-    let now = Utc::now();
-
-    let pr = |id: &str,
-              number: u32,
-              title: &str,
-              repo: &str,
-              author: &str,
-              bucket: Bucket,
-              ci: Option<&str>,
-              hours_ago: i64| CategorizedPr {
-        id: id.to_string(),
-        number,
-        title: title.to_string(),
-        url: format!("https://github.com/{repo}/pull/{number}"),
-        repo: repo.to_string(),
-        author: author.to_string(),
-        bucket,
-        created_at: Some(now - Duration::hours(hours_ago)),
-        updated_at: Some(now - Duration::hours(hours_ago / 2)),
-        last_commit_sha: Some(format!("abc{id}")),
-        last_commit_date: Some(now - Duration::hours(hours_ago / 2)),
-        ci_status: ci.map(String::from),
-    };
-
-    vec![
-        // Needs Your Review
-        pr(
-            "d1",
-            342,
-            "Add OAuth2 PKCE flow",
-            "acme/backend",
-            "olivia-dev",
-            Bucket::NeedsYourReview,
-            Some("SUCCESS"),
-            2,
-        ),
-        pr(
-            "d2",
-            187,
-            "Migrate users table to UUIDs",
-            "acme/backend",
-            "james-eng",
-            Bucket::NeedsYourReview,
-            Some("SUCCESS"),
-            5,
-        ),
-        pr(
-            "d3",
-            891,
-            "Add dark mode support",
-            "acme/web-app",
-            "sarah-ui",
-            Bucket::NeedsYourReview,
-            Some("PENDING"),
-            1,
-        ),
-        pr(
-            "d4",
-            56,
-            "Bump dependencies (Feb 2026)",
-            "acme/infra",
-            "dependabot",
-            Bucket::NeedsYourReview,
-            Some("SUCCESS"),
-            8,
-        ),
-        // Returned to You (changes requested)
-        pr(
-            "d5",
-            204,
-            "Refactor payment processing",
-            "acme/backend",
-            "demo-user",
-            Bucket::ReturnedToYou,
-            Some("FAILURE"),
-            24,
-        ),
-        pr(
-            "d6",
-            723,
-            "Fix race condition in queue worker",
-            "acme/backend",
-            "demo-user",
-            Bucket::ReturnedToYou,
-            Some("SUCCESS"),
-            48,
-        ),
-        // Approved
-        pr(
-            "d7",
-            445,
-            "Add retry logic to webhook delivery",
-            "acme/backend",
-            "demo-user",
-            Bucket::Approved,
-            Some("SUCCESS"),
-            3,
-        ),
-        pr(
-            "d8",
-            112,
-            "Update onboarding flow copy",
-            "acme/web-app",
-            "demo-user",
-            Bucket::Approved,
-            Some("SUCCESS"),
-            6,
-        ),
-        // Waiting for Reviewers
-        pr(
-            "d9",
-            890,
-            "Implement rate limiting middleware",
-            "acme/backend",
-            "demo-user",
-            Bucket::WaitingForReviewers,
-            Some("SUCCESS"),
-            12,
-        ),
-        pr(
-            "d10",
-            334,
-            "Add E2E tests for checkout",
-            "acme/web-app",
-            "demo-user",
-            Bucket::WaitingForReviewers,
-            Some("PENDING"),
-            4,
-        ),
-        // Waiting for Author
-        pr(
-            "d11",
-            567,
-            "Add GraphQL subscriptions",
-            "acme/backend",
-            "mike-gql",
-            Bucket::WaitingForAuthor,
-            Some("SUCCESS"),
-            72,
-        ),
-        // CI Failing (Drafts bucket used as example)
-        pr(
-            "d12",
-            901,
-            "WIP: New dashboard layout",
-            "acme/web-app",
-            "demo-user",
-            Bucket::Drafts,
-            None,
-            168,
-        ),
-        // Recently Merged
-        pr(
-            "d13",
-            200,
-            "Fix memory leak in connection pool",
-            "acme/backend",
-            "demo-user",
-            Bucket::RecentlyMerged,
-            Some("SUCCESS"),
-            26,
-        ),
-        pr(
-            "d14",
-            88,
-            "Add Terraform module for Redis",
-            "acme/infra",
-            "demo-user",
-            Bucket::RecentlyMerged,
-            Some("SUCCESS"),
-            50,
-        ),
-    ]
-}
-
 // ── Loading indicator ────────────────────────────────────────────────────────
 
 fn set_loading(app: &AppHandle, loading: bool) {
@@ -638,105 +1258,261 @@ fn set_loading(app: &AppHandle, loading: bool) {
 // ── Fetch + state ───────────────────────────────────────────────────────────
 
 fn do_fetch(app: &AppHandle) {
-    if is_demo() {
-        do_fetch_demo(app);
-    } else {
-        do_fetch_live(app);
-    }
-}
-
-fn do_fetch_demo(app: &AppHandle) {
     let app_state = app.state::<GhTrayState>();
+    let accounts = app_state.accounts();
     let config = app_state.config.lock().unwrap().clone();
 
-    let all_prs = demo_prs();
-    let filtered = github::filter_prs(all_prs.clone(), &config);
+    set_loading(app, true);
 
-    // Generate identicon avatars for all demo authors
-    let authors: Vec<String> = filtered
+    let mut all_prs: Vec<CategorizedPr> = Vec::new();
+    let mut truncated: Vec<String> = Vec::new();
+    let mut warnings: Vec<MenuWarning> = Vec::new();
+    let mut errors: Vec<String> = Vec::new();
+
+    // The team view is fetched once, with the first account that can fetch
+    let mut results: Vec<Result<AccountFetch, String>> = Vec::new();
+    for account in &accounts {
+        let with_team = !results.iter().any(Result::is_ok);
+        results.push(fetch_account(app, account, &config, with_team));
+    }
+    // One pause for all accounts, then ask again for merge states still UNKNOWN
+    if results
         .iter()
-        .map(|pr| pr.author.clone())
-        .collect::<std::collections::HashSet<_>>()
-        .into_iter()
-        .collect();
-    github::ensure_demo_avatars(&authors);
+        .flatten()
+        .any(|fetched| fetched.prs.iter().any(CategorizedPr::merge_state_unknown))
+    {
+        std::thread::sleep(MERGE_STATE_RETRY_DELAY);
+        for (account, result) in accounts.iter().zip(&mut results) {
+            if let Ok(fetched) = result
+                && let Err(e) =
+                    github::refresh_merge_states(account.backend.as_ref(), &mut fetched.prs)
+            {
+                logging::log_error(&format!("Failed to refresh merge states: {e:#}"));
+            }
+        }
+    }
 
-    *app_state.last_error.lock().unwrap() = None;
+    for (account, result) in accounts.iter().zip(results) {
+        // Only name the account when there's more than one
+        let name = (accounts.len() > 1).then(|| account_label(account));
+        match result.map(|fetched| record_account(app, account, &config, fetched)) {
+            Ok(fetched) => {
+                all_prs.extend(fetched.prs);
+                truncated.extend(fetched.truncated.iter().map(|t| {
+                    let label = match &name {
+                        Some(name) => format!("{name} {}", t.label),
+                        None => t.label.to_string(),
+                    };
+                    format!("{label}: showing {} of {}", t.shown, t.total)
+                }));
+                warnings.extend(fetched.api_errors.iter().map(|err| {
+                    logging::log_error(&err.to_string());
+                    MenuWarning {
+                        text: match &name {
+                            Some(name) => format!("{name}: {}", err.warning()),
+                            None => err.warning(),
+                        },
+                        url: err.help_url(account.backend.host()),
+                    }
+                }));
+            }
+            Err(e) => {
+                let msg = match &name {
+                    Some(name) => format!("{name}: {e}"),
+                    None => e,
+                };
+                logging::log_error(&msg);
+                errors.push(msg);
+                // Keep showing what this account had before
+                let previous = app_state.all_prs.lock().unwrap().clone();
+                all_prs.extend(
+                    previous.into_iter().filter(|pr| {
+                        pr.account == account.tag && pr.host == account.backend.host()
+                    }),
+                );
+            }
+        }
+    }
+
+    *app_state.last_error.lock().unwrap() = if errors.is_empty() {
+        None
+    } else {
+        Some(errors.join("; "))
+    };
+    *app_state.truncated.lock().unwrap() = truncated;
+    *app_state.warnings.lock().unwrap() = warnings;
+
+    let filtered = github::filter_prs(all_prs.clone(), &config);
     *app_state.all_prs.lock().unwrap() = all_prs;
     *app_state.prs.lock().unwrap() = filtered.clone();
 
     update_tray(app, &filtered, &config);
 }
 
-fn do_fetch_live(app: &AppHandle) {
-    let app_state = app.state::<GhTrayState>();
+/// How long to give GitHub to compute mergeability before asking again.
+const MERGE_STATE_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(3);
 
-    set_loading(app, true);
+/// What one account's fetch produced.
+struct AccountFetch {
+    prs: Vec<CategorizedPr>,
+    truncated: Vec<Truncation>,
+    /// Errors for the parts of the response GitHub couldn't resolve
+    api_errors: Vec<ApiError>,
+}
 
-    {
-        let mut viewer = app_state.viewer.lock().unwrap();
-        if viewer.is_none() {
-            match github::get_viewer_login() {
-                Ok(login) => *viewer = Some(login),
-                Err(e) => {
-                    let msg = format!("{e}");
-                    logging::log_error(&msg);
-                    *app_state.last_error.lock().unwrap() = Some(msg);
-                    let config = app_state.config.lock().unwrap().clone();
-                    let prs = app_state.prs.lock().unwrap().clone();
-                    update_tray(app, &prs, &config);
-                    return;
-                }
-            }
+/// Fetch and categorize one account's PRs. `with_team` adds the team
+/// view's PRs.
+fn fetch_account(
+    app: &AppHandle,
+    account: &Account,
+    config: &AppConfig,
+    with_team: bool,
+) -> Result<AccountFetch, String> {
+    let app_state = app.state::<GhTrayState>();
+    let backend = account.backend.as_ref();
+    let host = backend.host();
+    let key = account.key();
+
+    let cached_viewer = app_state.viewers.lock().unwrap().get(&key).cloned();
+    let viewer = match cached_viewer {
+        Some(viewer) => viewer,
+        None => {
+            let viewer = github::get_viewer(backend).map_err(|e| e.to_string())?;
+            app_state
+                .viewers
+                .lock()
+                .unwrap()
+                .insert(key.clone(), viewer.clone());
+            viewer
         }
-    }
+    };
 
-    let viewer_login = app_state.viewer.lock().unwrap().clone().unwrap_or_default();
-    let config = app_state.config.lock().unwrap().clone();
+    let team = if with_team && config.has_team_view() {
+        team_logins(&app_state, account, config)
+    } else {
+        Vec::new()
+    };
 
-    match github::fetch_prs(config.merged_window_days) {
-        Ok(response) => {
-            // Clear any previous error
-            *app_state.last_error.lock().unwrap() = None;
+    let response = github::fetch_prs(
+        backend,
+        config.merged_window_days,
+        config.max_results_per_search,
+        &config.saved_searches,
+        config.fetch_issues,
+        &team,
+    )
+    .map_err(|e| e.to_string())?;
+
+    if let Some(rate) = response.data.rate_limit {
+        app_state
+            .rate_limits
+            .lock()
+            .unwrap()
+            .insert(account_label(account), rate);
+    }
 
-            let all_prs = github::categorize_all(&response.data, &viewer_login);
-            let filtered = github::filter_prs(all_prs.clone(), &config);
+    Ok(AccountFetch {
+        prs: github::categorize_all(&response.data, host, &account.tag, &viewer, config),
+        truncated: response.data.truncated_searches(&config.saved_searches),
+        api_errors: response.api_errors(),
+    })
+}
 
-            let authors: Vec<String> = filtered
-                .iter()
-                .map(|pr| pr.author.clone())
-                .collect::<std::collections::HashSet<_>>()
-                .into_iter()
-                .collect();
-            github::ensure_avatars(&authors);
+/// Add the notifications inbox to one account's fetch and diff it against
+/// the last one, sending notifications for anything that changed.
+fn record_account(
+    app: &AppHandle,
+    account: &Account,
+    config: &AppConfig,
+    fetched: AccountFetch,
+) -> AccountFetch {
+    let app_state = app.state::<GhTrayState>();
+    let backend = account.backend.as_ref();
+    let host = backend.host();
+    let key = account.key();
+
+    let mut all_prs = fetched.prs;
+    if config.fetch_notifications {
+        // Work on a copy so mark-as-read clicks aren't blocked by the request
+        let mut inbox = app_state
+            .notifications
+            .lock()
+            .unwrap()
+            .get(&key)
+            .cloned()
+            .unwrap_or_default();
+        if let Err(e) = github::fetch_notifications(backend, &mut inbox) {
+            logging::log_error(&format!("Failed to fetch notifications: {e:#}"));
+        }
+        all_prs.extend(github::categorize_notifications(
+            &inbox.notifications,
+            host,
+            &account.tag,
+        ));
+        app_state
+            .notifications
+            .lock()
+            .unwrap()
+            .insert(key.clone(), inbox);
+    }
+    let filtered = github::filter_prs(all_prs.clone(), config);
 
-            let old_state = state::load_state();
-            let transitions = github::diff_states(&old_state.prs, &filtered);
+    let authors: Vec<String> = filtered
+        .iter()
+        .filter(|pr| !pr.author.is_empty())
+        .map(|pr| pr.author.clone())
+        .collect::<std::collections::HashSet<_>>()
+        .into_iter()
+        .collect();
+    backend.ensure_avatars(&authors);
 
-            if old_state.last_fetch.is_some() {
-                send_notifications(app, &transitions, &config);
-            }
+    let mut snapshots = app_state.snapshots.lock().unwrap();
+    // Offline backends start fresh instead of diffing against real state
+    let old_state = snapshots.entry(key.clone()).or_insert_with(|| {
+        if backend.is_live() {
+            state::load_state(&key)
+        } else {
+            state::AppState::default()
+        }
+    });
+    // Team PRs aren't the viewer's; they never notify
+    let personal: Vec<CategorizedPr> = filtered.into_iter().filter(|pr| !pr.is_team()).collect();
+    let transitions = github::diff_states(&old_state.prs, &personal);
 
-            let new_state = state::AppState {
-                last_fetch: Some(chrono::Utc::now()),
-                prs: filtered
-                    .iter()
-                    .map(|pr| (pr.id.clone(), pr.clone()))
-                    .collect(),
-            };
-            let _ = state::save_state(&new_state);
+    if old_state.last_fetch.is_some() && config.account_notifies(&account.tag) {
+        send_notifications(app, &transitions, config);
+    }
+
+    let new_state = state::AppState {
+        last_fetch: Some(chrono::Utc::now()),
+        prs: personal.iter().map(|pr| (pr.key(), pr.clone())).collect(),
+    };
+    if backend.is_live() {
+        let _ = state::save_state(&key, &new_state);
+    }
+    *old_state = new_state;
 
-            *app_state.all_prs.lock().unwrap() = all_prs;
-            *app_state.prs.lock().unwrap() = filtered.clone();
+    AccountFetch {
+        prs: all_prs,
+        ..fetched
+    }
+}
 
-            update_tray(app, &filtered, &config);
+/// The team view's logins, cached until the accounts or team settings
+/// change. If the team can't be resolved, falls back to the extra logins
+/// and tries again next poll.
+fn team_logins(app_state: &GhTrayState, account: &Account, config: &AppConfig) -> Vec<String> {
+    if let Some(logins) = app_state.team_logins.lock().unwrap().clone() {
+        return logins;
+    }
+    match github::team_logins(account.backend.as_ref(), config) {
+        Ok(logins) => {
+            *app_state.team_logins.lock().unwrap() = Some(logins.clone());
+            logins
         }
         Err(e) => {
-            let msg = format!("{e}");
-            logging::log_error(&msg);
-            *app_state.last_error.lock().unwrap() = Some(msg);
-            let prs = app_state.prs.lock().unwrap().clone();
-            update_tray(app, &prs, &config);
+            logging::log_error(&format!("Failed to resolve team members: {e:#}"));
+            config.team_members.clone()
         }
     }
 }
@@ -744,7 +1520,13 @@ fn do_fetch_live(app: &AppHandle) {
 fn update_tray(app: &AppHandle, prs: &[CategorizedPr], config: &AppConfig) {
     let count = prs
         .iter()
-        .filter(|pr| config.counts_for_badge(pr.bucket.id()))
+        .filter(|pr| {
+            !pr.is_team()
+                && config.counts_for_badge(pr.section_id())
+                && config.account_counts_for_badge(&pr.account)
+                && config.bot_counts_for_badge(pr.section_id(), &pr.author)
+                && config.label_counts_for_badge(&pr.labels)
+        })
         .count();
 
     if let Some(tray) = app.tray_by_id("main") {
@@ -793,11 +1575,41 @@ fn handle_menu_event(app: &AppHandle, id: &str) {
             std::thread::spawn(move || do_fetch(&app_clone));
         }
         "action_settings" => open_settings(app),
+        "read_all" => {
+            let app_clone = app.clone();
+            std::thread::spawn(move || mark_notifications_read(&app_clone, None));
+        }
         _ => {
-            if let Some(pr_id) = id.strip_prefix("pr_") {
+            if let Some(index) = id.strip_prefix("warning_") {
+                let state = app.state::<GhTrayState>();
+                let url = index
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| state.warnings.lock().unwrap().get(i)?.url.clone());
+                if let Some(url) = url {
+                    let _ = tauri_plugin_opener::open_url(&url, None::<&str>);
+                }
+            } else if let Some(rest) = id.strip_prefix("check_") {
+                let state = app.state::<GhTrayState>();
+                let url = rest.split_once('_').and_then(|(index, pr_id)| {
+                    let prs = state.prs.lock().unwrap();
+                    let pr = prs.iter().find(|p| p.key() == pr_id)?;
+                    pr.failing_checks
+                        .get(index.parse::<usize>().ok()?)?
+                        .details_url
+                        .clone()
+                });
+                if let Some(url) = url {
+                    let _ = tauri_plugin_opener::open_url(&url, None::<&str>);
+                }
+            } else if let Some(key) = id.strip_prefix("read_") {
+                let app_clone = app.clone();
+                let key = key.to_string();
+                std::thread::spawn(move || mark_notifications_read(&app_clone, Some(&key)));
+            } else if let Some(pr_id) = id.strip_prefix("pr_") {
                 let state = app.state::<GhTrayState>();
                 let prs = state.prs.lock().unwrap();
-                if let Some(pr) = prs.iter().find(|p| p.id == pr_id) {
+                if let Some(pr) = prs.iter().find(|p| p.key() == pr_id) {
                     let url = pr.url.clone();
                     drop(prs);
                     let _ = tauri_plugin_opener::open_url(&url, None::<&str>);
@@ -807,6 +1619,60 @@ fn handle_menu_event(app: &AppHandle, id: &str) {
     }
 }
 
+/// Mark one notification row (by key), or every account's inbox, as read
+/// and drop the rows right away instead of waiting for the next poll.
+fn mark_notifications_read(app: &AppHandle, key: Option<&str>) {
+    let state = app.state::<GhTrayState>();
+    let rows: Vec<CategorizedPr> = state
+        .prs
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|pr| pr.bucket == Bucket::Notifications && key.is_none_or(|key| pr.key() == key))
+        .cloned()
+        .collect();
+
+    let mut read = std::collections::HashSet::new();
+    for account in state.accounts() {
+        let account_rows: Vec<&CategorizedPr> = rows
+            .iter()
+            .filter(|pr| pr.account == account.tag && pr.host == account.backend.host())
+            .collect();
+        let backend = account.backend.as_ref();
+        let result = match (key, account_rows.as_slice()) {
+            (_, []) => continue,
+            (Some(_), [pr]) => github::mark_notification_read(backend, &pr.id),
+            _ => github::mark_all_notifications_read(backend),
+        };
+        if let Err(e) = result {
+            logging::log_error(&format!("Failed to mark notifications read: {e:#}"));
+            continue;
+        }
+        if let Some(inbox) = state.notifications.lock().unwrap().get_mut(&account.key()) {
+            for pr in &account_rows {
+                inbox.remove(&pr.id);
+            }
+        }
+        read.extend(account_rows.iter().map(|pr| pr.key()));
+    }
+    if read.is_empty() {
+        return;
+    }
+
+    state
+        .all_prs
+        .lock()
+        .unwrap()
+        .retain(|pr| !read.contains(&pr.key()));
+    let prs = {
+        let mut prs = state.prs.lock().unwrap();
+        prs.retain(|pr| !read.contains(&pr.key()));
+        prs.clone()
+    };
+    let config = state.config.lock().unwrap().clone();
+    update_tray(app, &prs, &config);
+}
+
 fn open_settings(app: &AppHandle) {
     if let Some(window) = app.get_webview_window("settings") {
         let _ = window.show();
@@ -831,8 +1697,8 @@ fn start_polling(app: AppHandle) {
         loop {
             let interval = {
                 let state = app.state::<GhTrayState>();
-                let config = state.config.lock().unwrap();
-                std::time::Duration::from_secs(config.poll_interval_secs_clamped())
+                let config = state.config.lock().unwrap().clone();
+                next_poll_delay(&state, &config)
             };
             std::thread::sleep(interval);
             do_fetch(&app);
@@ -840,19 +1706,56 @@ fn start_polling(app: AppHandle) {
     });
 }
 
+/// The configured poll interval, stretched when any account is running low
+/// on API budget.
+fn next_poll_delay(state: &GhTrayState, config: &AppConfig) -> std::time::Duration {
+    let base = std::time::Duration::from_secs(config.poll_interval_secs_clamped());
+    let now = chrono::Utc::now();
+    let delay = state
+        .rate_limits
+        .lock()
+        .unwrap()
+        .values()
+        .map(|rate| rate.poll_delay(base, config.rate_limit_threshold, now))
+        .max()
+        .unwrap_or(base);
+    if delay > base {
+        logging::log_error(&format!(
+            "API budget low, next poll in {}s instead of {}s",
+            delay.as_secs(),
+            base.as_secs()
+        ));
+    }
+    delay
+}
+
 // ── Startup checks ──────────────────────────────────────────────────────────
 
 fn check_startup(app: &AppHandle) {
-    if is_demo() {
+    let state = app.state::<GhTrayState>();
+    // Surface the first account that isn't usable
+    let failing = state
+        .accounts()
+        .into_iter()
+        .map(|account| {
+            let status = account.backend.status();
+            (account, status)
+        })
+        .find(|(_, status)| !matches!(status, GhStatus::Ok));
+    let Some((account, status)) = failing else {
+        // All good — start silently
         return;
-    }
-    match github::check_gh_status() {
-        GhStatus::Ok => {
-            // All good — start silently
-        }
+    };
+    let backend = account.backend.as_ref();
+    let name = if account.tag.is_empty() {
+        backend.name().to_string()
+    } else {
+        format!("{} ({})", backend.name(), account.tag)
+    };
+    match status {
+        GhStatus::Ok => {}
         GhStatus::NotInstalled => {
             logging::log_error("gh CLI not found");
-            let state = app.state::<GhTrayState>();
             *state.last_error.lock().unwrap() =
                 Some("gh CLI not installed. Install from https://cli.github.com".to_string());
             let config = state.config.lock().unwrap().clone();
@@ -860,14 +1763,20 @@ fn check_startup(app: &AppHandle) {
             open_settings(app);
         }
         GhStatus::NotAuthenticated(_) => {
-            logging::log_error("gh CLI not authenticated");
-            let state = app.state::<GhTrayState>();
-            *state.last_error.lock().unwrap() =
-                Some("gh not authenticated. Run `gh auth login` in terminal".to_string());
+            logging::log_error(&format!("{name} not authenticated"));
+            *state.last_error.lock().unwrap() = Some(format!(
+                "{name} not authenticated. {}",
+                backend.login_hint()
+            ));
             let config = state.config.lock().unwrap().clone();
             update_tray(app, &[], &config);
             open_settings(app);
         }
+        GhStatus::Unreachable(msg) => {
+            // Polling will retry; just surface the error
+            logging::log_error(&msg);
+            *state.last_error.lock().unwrap() = Some(msg);
+        }
     }
 }
 
@@ -888,21 +1797,53 @@ fn setup_tray(app: &AppHandle) {
 
 // ── Entry point ─────────────────────────────────────────────────────────────
 
+/// Value following `flag` on the command line (`--replay <dir>`).
+fn arg_value(flag: &str) -> Option<String> {
+    let mut args = std::env::args();
+    args.find(|a| a == flag)?;
+    args.next()
+}
+
 pub fn run() {
-    if std::env::args().any(|a| a == "--demo") {
-        DEMO_MODE.store(true, Ordering::Relaxed);
-    }
+    let mut replay_config = None;
+    let offline_accounts: Option<Vec<Account>> = if std::env::args().any(|a| a == "--demo") {
+        Some(vec![Account::untagged(Arc::new(DemoBackend))])
+    } else if let Some(dir) = arg_value("--replay") {
+        let dir = Path::new(&dir);
+        match backend::replay(dir).and_then(|accounts| {
+            replay_config = backend::load_capture_config(dir)?;
+            Ok(accounts)
+        }) {
+            Ok(accounts) => Some(accounts),
+            Err(e) => {
+                eprintln!("{e:#}");
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    let record_dir = arg_value("--record").map(PathBuf::from);
 
     let app = tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_single_instance::init(|_app, _args, _cwd| {}))
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_autostart::Builder::new().build())
-        .manage(GhTrayState::new())
+        .manage(GhTrayState::new(
+            offline_accounts,
+            replay_config,
+            record_dir,
+        ))
         .invoke_handler(tauri::generate_handler![
             get_settings,
             save_settings,
-            check_gh
+            save_token,
+            start_device_login,
+            device_login_status,
+            sign_out,
+            check_gh,
+            check_saved_search
         ])
         .setup(|app| {
             #[cfg(target_os = "macos")]